@@ -188,7 +188,9 @@ fn ast_union( ast: AstUnion ) -> TokenStream {
                 #item ( #item< 'a > ),
             } );
 
+        //  Variants are named after node types, e.g. `Pattern = TuplePattern | OrPattern`.
         quote! {
+            #[ allow( clippy::enum_variant_names ) ]
             pub enum #name < 'a > {
                 #( #items )*
             }
@@ -466,7 +468,7 @@ fn ast_record( ast: AstRecord ) -> TokenStream {
             .filter_map( | field | {
                 match &field.type_ {
                     AstRecordFieldType::Token( id ) => {
-                        Some( get_token_impl( &field.name, &id ) )
+                        Some( get_token_impl( &ast.name, &field.name, id ) )
                     }
 
                     AstRecordFieldType::Tokens( tokens ) => {
@@ -481,9 +483,9 @@ fn ast_record( ast: AstRecord ) -> TokenStream {
         }
     }
 
-    fn get_token_impl( field_name: &Ident, token: &Ident ) -> proc_macro2::TokenStream {
+    fn get_token_impl( ast_name: &Ident, field_name: &Ident, token: &Ident ) -> proc_macro2::TokenStream {
         quote! {
-            impl< 'a > Name< 'a > {
+            impl< 'a > #ast_name< 'a > {
                 pub fn #field_name( &'a self ) -> Option< &'a Token< 'a > > {
                     self.node
                         .find_token( TokenKind::#token )
@@ -509,7 +511,7 @@ fn ast_record( ast: AstRecord ) -> TokenStream {
                 #( #tokens )*
             ] );
 
-            impl<'a> InlineBinary<'a> {
+            impl<'a> #ast_name<'a> {
 
                 pub fn #field_name( &'a self ) -> Option< &'a Token< 'a > > {
                     self.node.find_token_in_set( #const_name )
@@ -520,7 +522,8 @@ fn ast_record( ast: AstRecord ) -> TokenStream {
     }
 
     fn get_var_name( id: &Ident ) -> Ident {
-        format_ident!( "{}", id.to_string().to_lowercase() )
+        //  Prefixed, lowercased node names may be keywords (`Type`, `Match`).
+        format_ident!( "nodes_{}", id.to_string().to_lowercase() )
     }
 
 }
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{ Path, PathBuf };
+
+use crate::{ SyntaxNode, SyntaxKind, AstNode, ConstEvaluator, ConstValue, Program };
+use crate::{ validate, resolve_methods, check_traits };
+use crate::ast;
+use crate::modules::{ import_path, source_file_items };
+
+//  Checks of a program loaded from the entry module, all modules and their imports:
+//  import resolution, validation, constants, method calls and trait impls.
+//
+//  Modules are checked in the load order, so values of imported constants are known. Method calls
+//  on imported types are left unresolved.
+//
+
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub start: usize,
+    pub end: usize,
+    pub message: String,
+}
+
+impl fmt::Debug for Diagnostic {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        write!( f , "ERROR: {} at {}..{} in {}", self.message, self.start, self.end, self.path.display() )
+    }
+
+}
+
+pub struct Analysis {
+    pub program: Program,
+    //  Grouped by modules in the load order, sorted by position in a module.
+    pub diagnostics: Vec< Diagnostic >,
+}
+
+pub fn analyze( entry: &Path ) -> Analysis {
+    let program = Program::load( entry );
+
+    let mut diagnostics = Vec::new();
+    //  Values of exported constants by module.
+    let mut consts: HashMap< &Path, HashMap< String, ConstValue > > = HashMap::new();
+
+    //  The entry module is missing.
+    for error in program.errors.iter().filter( | e | program.module( &e.path ).is_none() ) {
+        diagnostics.push( Diagnostic {
+            path: error.path.clone(),
+            start: error.start,
+            end: error.end,
+            message: format!( "{:?}", error.kind ),
+        } );
+    }
+
+    for module in &program.modules {
+        let node = module.parse();
+        let mut errors: Vec< ( usize, usize, String ) > = program.errors
+            .iter()
+            .filter( | e | e.path == module.path )
+            .map( | e | ( e.start, e.end, format!( "{:?}", e.kind ) ) )
+            .collect();
+
+        errors.extend( module.lexer_errors.iter().map( | e | ( e.start, e.end, format!( "{:?}", e.kind ) ) ) );
+        errors.extend( module.errors.iter().map( | e | ( e.start, e.end, format!( "{:?}", e.kind ) ) ) );
+
+        let mut eval = ConstEvaluator::new( &node );
+        for ( import, target ) in imports( &node, &module.path ) {
+            let Some( values ) = consts.get( target.as_path() ) else {
+                continue;
+            };
+            for name in import.names.iter() {
+                if let ( Some( name ), Some( local ) ) = ( name.name(), name.local_name() ) {
+                    if let Some( value ) = values.get( name.text ) {
+                        eval.define( local.text, value.clone() );
+                    }
+                }
+            }
+        }
+        errors.extend( eval.check( &node ).into_iter().map( | e | ( e.start, e.end, format!( "{:?}", e.kind ) ) ) );
+
+        let mut values = HashMap::new();
+        for name in exported_consts( &node ) {
+            if let Some( Ok( value ) ) = eval.value( name ) {
+                values.insert( name.to_string(), value );
+            }
+        }
+        consts.insert( module.path.as_path(), values );
+
+        errors.extend( validate( &node ).into_iter().map( | e | ( e.start, e.end, format!( "{:?}", e.kind ) ) ) );
+        errors.extend( resolve_methods( &node ).errors.into_iter().map( | e | ( e.start, e.end, format!( "{:?}", e.kind ) ) ) );
+        errors.extend( check_traits( &node ).into_iter().map( | e | ( e.start, e.end, format!( "{:?}", e.kind ) ) ) );
+
+        errors.sort_by_key( | ( start, end, _ ) | ( *start, *end ) );
+        diagnostics.extend( errors.into_iter().map( | ( start, end, message ) | Diagnostic {
+            path: module.path.clone(),
+            start,
+            end,
+            message,
+        } ) );
+    }
+
+    Analysis {
+        program,
+        diagnostics,
+    }
+}
+
+//  Imports with paths of the imported modules.
+//
+fn imports< 'a >( node: &'a SyntaxNode< 'a >, path: &Path ) -> Vec< ( ast::Import< 'a >, PathBuf ) > {
+    source_file_items( node )
+        .filter_map( ast::Import::cast )
+        .filter_map( | import | {
+            let target = import_path( path, &import )?;
+            Some( ( import, target ) )
+        } )
+        .collect()
+}
+
+fn exported_consts< 'a >( node: &'a SyntaxNode< 'a > ) -> Vec< &'a str > {
+    source_file_items( node )
+        .filter( | item | item.kind == SyntaxKind::Const && item.find_node_by_index( SyntaxKind::Visibility, 0 ).is_some() )
+        .filter_map( | item | item.name() )
+        .collect()
+}
+
+#[ cfg( test ) ]
+mod tests {
+    use super::*;
+
+    #[ test ]
+    fn analyze_fixtures() {
+        let dir = Path::new( env!( "CARGO_MANIFEST_DIR" ) ).join( "tests/modules" );
+
+        let analysis = analyze( &dir.join( "basic/main.teya" ) );
+        assert_eq!( analysis.program.modules.len(), 3 );
+        assert!( analysis.diagnostics.is_empty(), "{:?}", analysis.diagnostics );
+
+        let analysis = analyze( &dir.join( "checks/main.teya" ) );
+        let diagnostics: Vec< ( String, String ) > = analysis.diagnostics
+            .iter()
+            .map( | d | {
+                let source = &analysis.program.module( &d.path ).unwrap().source;
+                ( d.path.file_name().unwrap().to_string_lossy().to_string(), source[ d.start .. d.end ].to_string() )
+            } )
+            .collect();
+        assert_eq!( diagnostics, vec![
+            ( "consts.teya".to_string(), "1 / 0".to_string() ),
+            ( "main.teya".to_string(), "Hidden".to_string() ),
+            ( "main.teya".to_string(), "Unknown".to_string() ),
+            ( "main.teya".to_string(), "break".to_string() ),
+            ( "main.teya".to_string(), "area".to_string() ),
+            ( "main.teya".to_string(), ":+".to_string() ),
+        ] );
+
+        let messages: Vec< &str > = analysis.diagnostics.iter().map( | d | d.message.as_str() ).collect();
+        assert_eq!( messages, vec![
+            "Division by zero in constant expression",
+            "Item Hidden is not exported",
+            "Unknown constant Unknown",
+            "`break` outside of a loop",
+            "No method area in Point",
+            "Format sign is not applicable to String",
+        ] );
+
+        //  Syntax errors of an imported module.
+        let analysis = analyze( &dir.join( "syntax/main.teya" ) );
+        let diagnostics: Vec< ( String, String, String ) > = analysis.diagnostics
+            .iter()
+            .map( | d | {
+                let source = &analysis.program.module( &d.path ).unwrap().source;
+                ( d.path.file_name().unwrap().to_string_lossy().to_string(), source[ d.start .. d.end ].to_string(), d.message.clone() )
+            } )
+            .collect();
+        assert_eq!( diagnostics, vec![
+            ( "broken.teya".to_string(), "=".to_string(), "Token required Id".to_string() ),
+            ( "broken.teya".to_string(), "\\q".to_string(), "Invalid escape sequence \\q".to_string() ),
+        ] );
+
+        let analysis = analyze( &dir.join( "missing.teya" ) );
+        assert_eq!( analysis.diagnostics.len(), 1 );
+    }
+
+}
@@ -1,6 +1,34 @@
 use crate::{ SyntaxNode, SyntaxKind, SyntaxElement, Token, TokenKind, TokenSet, AstNode };
+use crate::unescape::{ unescape, strip_indent, raw_string_value };
+use crate::number::{ Number, NumberError, NumberValue, parse_number };
+use crate::format_spec::{ FormatSpec, FormatSpecError, ValueKind, parse_format_spec };
 use gen_ast::ast;
 
+use std::borrow::Cow;
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+//  Items with `///` comments, `parser_event::process` moves the comments into the item node.
+//
+pub trait HasDocs< 'a >: AstNode< 'a > {
+
+    fn docs( &'a self ) -> Option< std::string::String > {
+        self.syntax().docs( false )
+    }
+
+}
+
+impl < 'a > HasDocs< 'a > for Fn< 'a > {}
+impl < 'a > HasDocs< 'a > for Struct< 'a > {}
+impl < 'a > HasDocs< 'a > for StructMethod< 'a > {}
+impl < 'a > HasDocs< 'a > for Enum< 'a > {}
+impl < 'a > HasDocs< 'a > for TypeAlias< 'a > {}
+impl < 'a > HasDocs< 'a > for Const< 'a > {}
+impl < 'a > HasDocs< 'a > for Trait< 'a > {}
+impl < 'a > HasDocs< 'a > for TraitMethod< 'a > {}
+impl < 'a > HasDocs< 'a > for Impl< 'a > {}
+impl < 'a > HasDocs< 'a > for Import< 'a > {}
+
 //  ---------------------------------------------------------------------------------------------------------------  //
 
 ast! {
@@ -15,6 +43,15 @@ ast! {
     }
 }
 
+impl < 'a > SourceFile< 'a > {
+
+    //  `//!` comments of the file.
+    pub fn docs( &self ) -> Option< std::string::String > {
+        self.node.docs( true )
+    }
+
+}
+
 ast! {
     Fn {
         name: Name,
@@ -38,9 +75,1440 @@ ast! {
 ast! {
     Struct {
         name: Name,
+        items: StructItems,
+    }
+}
+
+ast! {
+    StructItems {
+        fields: *StructField,
+        methods: *StructMethod,
+    }
+}
+
+ast! {
+    StructField {
+        name: #Id,
+    }
+}
+
+ast! {
+    StructMethod {
+        name: Name,
+        params: FnParams,
+        return_type: FnReturnType,
+        body: Block,
+    }
+}
+
+impl < 'a > StructMethod< 'a > {
+
+    pub fn receiver( &self ) -> Option< &SelfParam< 'a > > {
+        self.params.as_ref()?.self_param.as_ref()
+    }
+
+    //  Methods without `self` are called on the struct: `Point.new()`.
+    pub fn is_static( &self ) -> bool {
+        self.receiver().is_none()
+    }
+
+}
+
+ast! {
+    FnParams {
+        self_param: SelfParam,
+        params: *FnParam,
+    }
+}
+
+ast! {
+    SelfParam {
+        amp: #Amp,
+    }
+}
+
+impl < 'a > SelfParam< 'a > {
+
+    //  `&self`
+    pub fn is_ref( &self ) -> bool {
+        self.amp().is_some()
+    }
+
+}
+
+ast! {
+    FnParam {
+        name: #Id,
+        ty: Type,
+    }
+}
+
+ast! {
+    Enum {
+        name: Name,
+    }
+}
+
+ast! {
+    TypeAlias {
+        name: Name,
+    }
+}
+
+ast! {
+    Const {
+        name: Name,
+        ty: Type,
+    }
+}
+
+impl < 'a > Const< 'a > {
+
+    //  The expression after `=`.
+    pub fn value( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.children
+            .iter()
+            .skip_while( | e | !matches!( e, SyntaxElement::Token( token ) if token.kind == TokenKind::Eq ) )
+            .find_map( | e | match e {
+                SyntaxElement::Node( node ) => Some( node ),
+                _ => None,
+            } )
+    }
+
+}
+
+ast! {
+    Trait {
+        name: Name,
+        generic_params: GenericParams,
+        items: TraitItems,
+    }
+}
+
+ast! {
+    TraitItems {
+        methods: *TraitMethod,
+    }
+}
+
+ast! {
+    TraitMethod {
+        name: Name,
+        params: FnParams,
+        return_type: FnReturnType,
+        body: Block,
+    }
+}
+
+impl < 'a > TraitMethod< 'a > {
+
+    pub fn receiver( &self ) -> Option< &SelfParam< 'a > > {
+        self.params.as_ref()?.self_param.as_ref()
+    }
+
+    //  Default methods have a body.
+    pub fn has_default( &self ) -> bool {
+        self.body.is_some()
+    }
+
+}
+
+ast! {
+    Impl {
+        trait_ref: TypeRef,
+        target: Type,
+        items: ImplItems,
+    }
+}
+
+ast! {
+    ImplItems {
+        methods: *StructMethod,
+    }
+}
+
+ast! {
+    GenericParams {
+        params: *GenericParam,
+    }
+}
+
+ast! {
+    GenericParam {
+        name: Name,
+    }
+}
+
+ast! {
+    TypeRef {
+        name: #Id,
+        generic_args: GenericArgs,
+    }
+}
+
+ast! {
+    GenericArgs {
+        args: *GenericArg,
+    }
+}
+
+ast! {
+    GenericArg {
+        ty: Type,
+    }
+}
+
+ast! {
+    Import {
+        names: *ImportName,
+        path: String,
+    }
+}
+
+impl < 'a > Import< 'a > {
+
+    //  `./other` of `import { a } from "./other"`, `None` with interpolations.
+    pub fn path_value( &self ) -> Option< std::string::String > {
+        self.path.as_ref()?.value().map( | x | x.into_owned() )
+    }
+
+}
+
+ast! {
+    ImportName {}
+}
+
+impl < 'a > ImportName< 'a > {
+
+    //  `a` of `a as b`.
+    pub fn name( &self ) -> Option< &'a Token< 'a > > {
+        self.node.nodes().next()?.find_token( TokenKind::Id )
+    }
+
+    //  `b` of `a as b`.
+    pub fn alias( &self ) -> Option< &'a Token< 'a > > {
+        self.node.nodes().nth( 1 )?.find_token( TokenKind::Id )
+    }
+
+    //  Name in the importing module.
+    pub fn local_name( &self ) -> Option< &'a Token< 'a > > {
+        self.alias().or_else( || self.name() )
+    }
+
+}
+
+ast! {
+    Item = Fn | Struct | Enum | TypeAlias | Const | Trait | Impl | Import
+}
+
+impl < 'a > Item< 'a > {
+
+    //  `pub` or `export` items.
+    pub fn is_exported( &self ) -> bool {
+        let node = match self {
+            Item::Fn( x ) => x.node,
+            Item::Struct( x ) => x.node,
+            Item::Enum( x ) => x.node,
+            Item::TypeAlias( x ) => x.node,
+            Item::Const( x ) => x.node,
+            Item::Trait( x ) => x.node,
+            Item::Impl( x ) => x.node,
+            Item::Import( x ) => x.node,
+        };
+
+        node.find_node_by_index( SyntaxKind::Visibility, 0 ).is_some()
+    }
+
+}
+
+ast! {
+    String {
+        quote: #( Quote | TripleQuote ),
+        raw: #RawString,
+        fragments: *StringFragment,
+        exprs: *StringExpr,
+    }
+}
+
+ast! {
+    StringFragment {
+        fragment: #StringFragment,
+    }
+}
+
+ast! {
+    StringExpr {
+        spec: #FormatSpec,
+    }
+}
+
+impl < 'a > StringExpr< 'a > {
+
+    //  Parsed format spec of `${ x:spec }`, if any.
+    pub fn format_spec( &'a self ) -> Option< Result< FormatSpec, FormatSpecError > > {
+        self.spec().map( | token | parse_format_spec( &token.text[ 1 .. ] ) )
+    }
+
+    //  Type of the interpolated expression if it is a literal.
+    //  Types of variables are resolved by `validation::validate`, which checks the spec.
+    pub fn value_kind( &self ) -> Option< ValueKind > {
+        literal_kind( self.node.nodes().next()? )
+    }
+
+}
+
+pub fn literal_kind( expr: &SyntaxNode ) -> Option< ValueKind > {
+    match expr.kind {
+        SyntaxKind::InlineNumber => match InlineNumber::cast( expr )?.value() {
+            Ok( Number { value: NumberValue::Int( _ ), .. } ) => Some( ValueKind::Int ),
+            Ok( Number { value: NumberValue::Float( _ ), .. } ) => Some( ValueKind::Float ),
+            Err( _ ) => None,
+        },
+        SyntaxKind::String => Some( ValueKind::String ),
+        SyntaxKind::InlineBool => Some( ValueKind::Bool ),
+        _ => None,
+    }
+}
+
+impl < 'a > String< 'a > {
+
+    //  Decoded value of a string without interpolations.
+    pub fn value( &'a self ) -> Option< Cow< 'a, str > > {
+        if let Some( raw ) = self.raw() {
+            return Some( Cow::Borrowed( raw_string_value( raw.text ) ) );
+        }
+
+        if !self.exprs.is_empty() {
+            return None;
+        }
+
+        let mut values = self.fragment_values();
+        match values.len() {
+            0 => Some( Cow::Borrowed( "" ) ),
+            1 => values.pop(),
+            _ => Some( Cow::Owned( values.into_iter().collect() ) ),
+        }
+    }
+
+    pub fn is_multiline( &'a self ) -> bool {
+        self.quote().is_some_and( | quote | quote.kind == TokenKind::TripleQuote )
+    }
+
+    //  Decoded values of fragments. Indentation of `"""` strings is stripped.
+    pub fn fragment_values( &'a self ) -> Vec< Cow< 'a, str > > {
+        if !self.is_multiline() {
+            return self.fragments
+                .iter()
+                .map( | x | x.value() )
+                .collect();
+        }
+
+        let parts: Vec< Option< &'a str > > = self.node.children
+            .iter()
+            .filter_map( | e | match e {
+                SyntaxElement::Node( node ) => match node.kind {
+                    SyntaxKind::StringFragment => Some( Some( node.find_token( TokenKind::StringFragment ).map_or( "", | t | t.text ) ) ),
+                    SyntaxKind::StringExpr => Some( None ),
+                    _ => None,
+                },
+                _ => None,
+            } )
+            .collect();
+
+        strip_indent( &parts )
+            .into_iter()
+            .flatten()
+            .map( | text | match text {
+                Cow::Borrowed( text ) => unescape( text, | _, _, _ | {} ),
+                Cow::Owned( text ) => Cow::Owned( unescape( &text, | _, _, _ | {} ).into_owned() ),
+            } )
+            .collect()
+    }
+
+}
+
+impl < 'a > StringFragment< 'a > {
+
+    //  Decoded value of the fragment. Invalid escapes are reported by the lexer and kept as is.
+    pub fn value( &'a self ) -> Cow< 'a, str > {
+        match self.fragment() {
+            Some( token ) => unescape( token.text, | _, _, _ | {} ),
+            None => Cow::Borrowed( "" ),
+        }
+    }
+
+}
+
+ast! {
+    InlineNumber {
+        number: #Number,
+    }
+}
+
+impl < 'a > InlineNumber< 'a > {
+
+    pub fn value( &'a self ) -> Result< Number, NumberError > {
+        self.signed_value( false )
+    }
+
+    //  `negative` if the literal is the operand of unary `-`.
+    //
+    pub fn signed_value( &'a self, negative: bool ) -> Result< Number, NumberError > {
+        match self.number() {
+            Some( token ) => parse_number( token.text, negative ),
+            None => Err( NumberError::MissingDigits ),
+        }
+    }
+
+}
+
+ast! {
+    Block {}
+}
+
+ast! {
+    Type {}
+}
+
+ast! {
+    TypeArray {
+        ty: Type,
+    }
+}
+
+impl < 'a > TypeArray< 'a > {
+
+    //  `N` of `[ T; N ]`, a constant expression.
+    pub fn size( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().nth( 1 )
+    }
+
+}
+
+ast! {
+    FnReturnType {
+        ty: Type,
+    }
+}
+
+ast! {
+    Closure {
+        params: ClosureParams,
+        return_type: FnReturnType,
+    }
+}
+
+impl < 'a > Closure< 'a > {
+
+    //  `Block` or an inline expression.
+    pub fn body( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.children
+            .iter()
+            .filter_map( | e | match e {
+                SyntaxElement::Node( node ) if !matches!( node.kind, SyntaxKind::ClosureParams | SyntaxKind::FnReturnType ) => Some( node ),
+                _ => None,
+            } )
+            .next()
+    }
+
+}
+
+ast! {
+    ClosureParams {
+        params: *ClosureParam,
+    }
+}
+
+ast! {
+    ClosureParam {
+        name: #Id,
+        ty: Type,
+    }
+}
+
+ast! {
+    If {
+        then_branch: Block,
+    }
+}
+
+//  `else { }` or `else if`.
+pub enum ElseBranch< 'a > {
+    Block( Block< 'a > ),
+    If( If< 'a > ),
+}
+
+impl < 'a > If< 'a > {
+
+    //  Not a field since `If` would contain itself.
+    pub fn else_branch( &self ) -> Option< ElseBranch< 'a > > {
+        let node = self.node.find_nodes( SyntaxKind::Else ).into_iter().next()?;
+
+        node.children.iter().find_map( | e | match e {
+            SyntaxElement::Node( node ) => match node.kind {
+                SyntaxKind::Block => Block::cast( node ).map( ElseBranch::Block ),
+                SyntaxKind::If => If::cast( node ).map( ElseBranch::If ),
+                _ => None,
+            },
+            _ => None,
+        } )
+    }
+
+}
+
+ast! {
+    While {
+        label: LoopLabel,
+        body: Block,
     }
 }
 
 ast! {
-    Item = Fn | Struct
+    For {
+        label: LoopLabel,
+        pattern: Pattern,
+        body: Block,
+    }
+}
+
+impl < 'a > For< 'a > {
+
+    //  The expression after `in`.
+    pub fn iterable( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.children
+            .iter()
+            .skip_while( | e | !matches!( e, SyntaxElement::Token( token ) if token.kind == TokenKind::In ) )
+            .find_map( | e | match e {
+                SyntaxElement::Node( node ) if node.kind != SyntaxKind::Block => Some( node ),
+                _ => None,
+            } )
+    }
+
+}
+
+ast! {
+    Pattern = WildcardPattern | BindingPattern | LiteralPattern | TuplePattern | VariantPattern | RecordPattern | OrPattern
+}
+
+ast! {
+    WildcardPattern {}
+}
+
+ast! {
+    BindingPattern {
+        name: Name,
+    }
+}
+
+ast! {
+    LiteralPattern {}
+}
+
+impl < 'a > LiteralPattern< 'a > {
+
+    //  `InlineNumber`, `InlineUnary` (negative number), `String`, `InlineBool` or `InlineNull`.
+    pub fn literal( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().next()
+    }
+
+}
+
+ast! {
+    TuplePattern {
+        patterns: *Pattern,
+    }
+}
+
+ast! {
+    VariantPattern {
+        name: Name,
+        patterns: *Pattern,
+    }
+}
+
+ast! {
+    RecordPattern {
+        name: Name,
+        fields: *RecordPatternField,
+        rest: #DotDot,
+    }
+}
+
+impl < 'a > RecordPattern< 'a > {
+
+    //  `Point { x, .. }`
+    pub fn has_rest( &'a self ) -> bool {
+        self.rest().is_some()
+    }
+
+}
+
+ast! {
+    RecordPatternField {
+        name: Name,
+        pattern: Pattern,
+    }
+}
+
+ast! {
+    OrPattern {
+        patterns: *Pattern,
+    }
+}
+
+ast! {
+    Match {
+        arms: *MatchArm,
+    }
+}
+
+impl < 'a > Match< 'a > {
+
+    pub fn scrutinee( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().next()
+    }
+
+}
+
+ast! {
+    MatchArm {
+        pattern: Pattern,
+        guard: MatchGuard,
+    }
+}
+
+impl < 'a > MatchArm< 'a > {
+
+    //  Expression, block or jump after `=>`.
+    pub fn body( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.children
+            .iter()
+            .skip_while( | e | !matches!( e, SyntaxElement::Token( token ) if token.kind == TokenKind::EqGt ) )
+            .find_map( | e | match e {
+                SyntaxElement::Node( node ) => Some( node ),
+                _ => None,
+            } )
+    }
+
+}
+
+ast! {
+    MatchGuard {}
+}
+
+impl < 'a > MatchGuard< 'a > {
+
+    pub fn condition( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().next()
+    }
+
+}
+
+ast! {
+    InlineRange {
+        op: #( DotDot | DotDotDot ),
+    }
+}
+
+impl < 'a > InlineRange< 'a > {
+
+    //  `a ... b` includes `b`.
+    pub fn is_inclusive( &'a self ) -> bool {
+        self.op().is_some_and( | op | op.kind == TokenKind::DotDotDot )
+    }
+
+}
+
+ast! {
+    InlineArray {}
+}
+
+impl < 'a > InlineArray< 'a > {
+
+    pub fn elements( &self ) -> Vec< &'a SyntaxNode< 'a > > {
+        self.node.nodes().collect()
+    }
+
+}
+
+ast! {
+    InlineArrayRepeat {}
+}
+
+impl < 'a > InlineArrayRepeat< 'a > {
+
+    //  `x` of `[ x; n ]`.
+    pub fn value( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().next()
+    }
+
+    //  `n` of `[ x; n ]`.
+    pub fn count( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().nth( 1 )
+    }
+
+}
+
+ast! {
+    InlineTuple {}
+}
+
+impl < 'a > InlineTuple< 'a > {
+
+    pub fn elements( &self ) -> Vec< &'a SyntaxNode< 'a > > {
+        self.node.nodes().collect()
+    }
+
+}
+
+ast! {
+    InlineIndex {}
+}
+
+impl < 'a > InlineIndex< 'a > {
+
+    pub fn base( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().next()
+    }
+
+    pub fn index( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().nth( 1 )
+    }
+
+}
+
+ast! {
+    InlineTupleField {
+        number: #Number,
+    }
+}
+
+impl < 'a > InlineTupleField< 'a > {
+
+    pub fn base( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().next()
+    }
+
+    //  `0` of `t.0`.
+    pub fn index( &'a self ) -> Option< usize > {
+        self.number()?.text.parse().ok()
+    }
+
+}
+
+ast! {
+    InlineStruct {
+        fields: *InlineStructField,
+        spread: InlineStructSpread,
+    }
+}
+
+impl < 'a > InlineStruct< 'a > {
+
+    //  `Foo` or `Shape.Circle`, `InlineVar` or `InlineField`.
+    pub fn path( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().next()
+    }
+
+}
+
+ast! {
+    InlineStructField {
+        name: Name,
+    }
+}
+
+impl < 'a > InlineStructField< 'a > {
+
+    //  `None` for the `Foo { x }` shorthand.
+    pub fn value( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().nth( 1 )
+    }
+
+}
+
+ast! {
+    InlineStructSpread {}
+}
+
+impl < 'a > InlineStructSpread< 'a > {
+
+    //  `base` of `..base`.
+    pub fn value( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().next()
+    }
+
+}
+
+ast! {
+    Assign {
+        op: #( Eq | PlusEq | MinusEq | StarEq | SlashEq | PercentEq | AmpAmpEq | PipePipeEq ),
+    }
+}
+
+impl < 'a > Assign< 'a > {
+
+    pub fn target( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().next()
+    }
+
+    pub fn value( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().nth( 1 )
+    }
+
+    //  `+` of `+=`, `None` for `=`.
+    pub fn binary_op( &'a self ) -> Option< TokenKind > {
+        match self.op()?.kind {
+            TokenKind::PlusEq => Some( TokenKind::Plus ),
+            TokenKind::MinusEq => Some( TokenKind::Minus ),
+            TokenKind::StarEq => Some( TokenKind::Star ),
+            TokenKind::SlashEq => Some( TokenKind::Slash ),
+            TokenKind::PercentEq => Some( TokenKind::Percent ),
+            TokenKind::AmpAmpEq => Some( TokenKind::AmpAmp ),
+            TokenKind::PipePipeEq => Some( TokenKind::PipePipe ),
+            _ => None,
+        }
+    }
+
+}
+
+ast! {
+    InlineMethodCall {
+        name: #Id,
+        args: CallArgs,
+    }
+}
+
+impl < 'a > InlineMethodCall< 'a > {
+
+    //  `a` of `a.foo()`.
+    pub fn receiver( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().next()
+    }
+
+}
+
+ast! {
+    CallArgs {
+        args: *CallArg,
+    }
+}
+
+ast! {
+    CallArg {}
+}
+
+impl < 'a > CallArg< 'a > {
+
+    pub fn value( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().next()
+    }
+
+}
+
+ast! {
+    InlineBool {
+        literal: #( True | False ),
+    }
+}
+
+impl < 'a > InlineBool< 'a > {
+
+    pub fn value( &'a self ) -> bool {
+        self.literal().is_some_and( | token | token.kind == TokenKind::True )
+    }
+
+}
+
+ast! {
+    InlineNull {}
+}
+
+ast! {
+    LoopLabel {
+        label: #Label,
+    }
+}
+
+impl < 'a > LoopLabel< 'a > {
+
+    //  Name of the label without `'`.
+    pub fn name( &'a self ) -> Option< &'a str > {
+        self.label().map( | token | &token.text[ 1 .. ] )
+    }
+
+}
+
+ast! {
+    Return {}
+}
+
+ast! {
+    Break {
+        label: #Label,
+    }
+}
+
+ast! {
+    Continue {
+        label: #Label,
+    }
+}
+
+impl < 'a > Return< 'a > {
+
+    pub fn value( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().next()
+    }
+
+}
+
+impl < 'a > Break< 'a > {
+
+    pub fn value( &self ) -> Option< &'a SyntaxNode< 'a > > {
+        self.node.nodes().next()
+    }
+
+}
+
+#[ cfg( test ) ]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::grammar::r_source_file;
+
+    #[ test ]
+    fn docs() {
+        let input = "//! File docs.\n\n/// Adds.\n///\n/// Two numbers.\n// Not a doc.\nfn add( x: number ) /* c */ {\n    x\n}\n\n/// Foo.\nstruct Foo {}\n\nenum Bar {}\n";
+        let node = Parser::new( input ).parse( r_source_file );
+        let root = Root::cast( &node ).unwrap();
+        let source_file = root.source_file.as_ref().unwrap();
+
+        assert_eq!( source_file.docs().as_deref(), Some( "File docs." ) );
+
+        let docs: Vec< Option< std::string::String > > = source_file.items
+            .iter()
+            .map( | item | match item {
+                Item::Fn( x ) => x.docs(),
+                Item::Struct( x ) => x.docs(),
+                Item::Enum( x ) => x.docs(),
+                Item::TypeAlias( x ) => x.docs(),
+                Item::Const( x ) => x.docs(),
+                Item::Trait( x ) => x.docs(),
+                Item::Impl( x ) => x.docs(),
+                Item::Import( x ) => x.docs(),
+            } )
+            .collect();
+        assert_eq!( docs, vec![
+            Some( "Adds.\n\nTwo numbers.".to_string() ),
+            Some( "Foo.".to_string() ),
+            None,
+        ] );
+
+        let input = "struct Point {\n    x: number,\n    /// Creates.\n    fn new( x: number ) -> Point { Point { x } }\n    fn len( &self ) -> number { self.x }\n}\n\ntrait Show {\n    /// Shows.\n    fn show( &self ) -> string;\n    fn print( &self ) { print( self.show() ) }\n}\n";
+        let node = Parser::new( input ).parse( r_source_file );
+        assert_eq!( node.text(), input );
+
+        let root = Root::cast( &node ).unwrap();
+        let items = &root.source_file.as_ref().unwrap().items;
+
+        let Item::Struct( s ) = &items[ 0 ] else { panic!() };
+        let docs: Vec< Option< std::string::String > > = s.items.as_ref().unwrap().methods.iter().map( | m | m.docs() ).collect();
+        assert_eq!( docs, vec![ Some( "Creates.".to_string() ), None ] );
+
+        let Item::Trait( t ) = &items[ 1 ] else { panic!() };
+        let docs: Vec< Option< std::string::String > > = t.items.as_ref().unwrap().methods.iter().map( | m | m.docs() ).collect();
+        assert_eq!( docs, vec![ Some( "Shows.".to_string() ), None ] );
+    }
+
+    #[ test ]
+    fn string_values() {
+        let input = "fn f() {\n    \"a\\tb\"\n    \"\"\"\n        Hello,\n          \\\"world\\\"\n        \"\"\"\n    r#\"raw \\n \"${}\"\"#\n    \"\"\"\n        x ${ y +\n        1 }\n        \"\"\"\n}\n";
+        let node = Parser::new( input ).parse( r_source_file );
+
+        let strings = node.descendants( SyntaxKind::String );
+
+        let values: Vec< Option< std::string::String > > = strings
+            .iter()
+            .map( | node | String::cast( node ).unwrap().value().map( | x | x.into_owned() ) )
+            .collect();
+        assert_eq!( values, vec![
+            Some( "a\tb".to_string() ),
+            Some( "Hello,\n  \"world\"".to_string() ),
+            Some( "raw \\n \"${}\"".to_string() ),
+            None,
+        ] );
+
+        let string = String::cast( strings[ 3 ] ).unwrap();
+        assert_eq!( string.fragment_values(), vec![ "x ", "" ] );
+    }
+
+    #[ test ]
+    fn literals() {
+        let input = "fn f() {\n    true\n    false\n    null\n}\n";
+        let node = Parser::new( input ).parse( r_source_file );
+
+        let bools = node.descendants( SyntaxKind::InlineBool );
+        let values: Vec< bool > = bools.iter().map( | node | InlineBool::cast( node ).unwrap().value() ).collect();
+        assert_eq!( values, vec![ true, false ] );
+
+        let nulls = node.descendants( SyntaxKind::InlineNull );
+        assert_eq!( nulls.len(), 1 );
+    }
+
+    #[ test ]
+    fn format_specs() {
+        let input = "fn f( price: number ) {\n    \"${ 1.5:.2 } ${ 42:#x } ${ \"s\":>10 } ${ 42:.2 } ${ x:.2 } ${ true }\"\n    let name = \"s\"\n    \"${ price:.2 } ${ name:+ }\"\n}\n";
+        let node = Parser::new( input ).parse( r_source_file );
+
+        let exprs = node.descendants( SyntaxKind::StringExpr );
+        let exprs: Vec< StringExpr > = exprs.iter().map( | node | StringExpr::cast( node ).unwrap() ).collect();
+
+        assert_eq!( exprs[ 0 ].format_spec(), Some( Ok( FormatSpec { precision: Some( 2 ), ..FormatSpec::default() } ) ) );
+        assert_eq!( exprs[ 5 ].format_spec(), None );
+
+        let kinds: Vec< Option< ValueKind > > = exprs.iter().map( | x | x.value_kind() ).collect();
+        assert_eq!( kinds, vec![
+            Some( ValueKind::Float ),
+            Some( ValueKind::Int ),
+            Some( ValueKind::String ),
+            Some( ValueKind::Int ),
+            None,
+            Some( ValueKind::Bool ),
+            //  Variables are not literals.
+            None,
+            None,
+        ] );
+    }
+
+    #[ test ]
+    fn if_else() {
+        let input = "fn f() {\n    if a { 1 }\n    // c\n    else if b {\n        2\n    }\n    else { 3 }\n    if c { 4 }\n    x\n}\n\nfn g():\n    if a:\n        1\n    else:\n        2\n";
+        let node = Parser::new( input ).parse( r_source_file );
+        assert_eq!( node.text(), input );
+
+        let ifs = node.descendants( SyntaxKind::If );
+        let ifs: Vec< If > = ifs.iter().map( | node | If::cast( node ).unwrap() ).collect();
+
+        //  `if a`, `else if b`, `if c`, `if a:`.
+        assert_eq!( ifs.len(), 4 );
+        assert_eq!( ifs[ 0 ].then_branch.as_ref().unwrap().syntax().text(), "{ 1 }\n    // c\n    " );
+
+        let Some( ElseBranch::If( else_if ) ) = ifs[ 0 ].else_branch() else { panic!() };
+        assert_eq!( else_if.syntax().text().trim(), "if b {\n        2\n    }\n    else { 3 }" );
+
+        let Some( ElseBranch::Block( else_block ) ) = else_if.else_branch() else { panic!() };
+        assert_eq!( else_block.syntax().text().trim(), "{ 3 }" );
+
+        assert!( ifs[ 2 ].else_branch().is_none() );
+        assert!( matches!( ifs[ 3 ].else_branch(), Some( ElseBranch::Block( _ ) ) ) );
+
+        //  Statements of `f`: `if`, `if`, `x`.
+        let blocks = node.descendants( SyntaxKind::Block );
+        let statements = blocks[ 0 ].children.iter().filter( | e | matches!( e, SyntaxElement::Node( _ ) ) ).count();
+        assert_eq!( statements, 3 );
+    }
+
+    #[ test ]
+    fn continuation_lines() {
+        let input = "type X =\n    number\nfn g() {}\nconst Y =\n    1\nfn f() {\n    if x:\n        y\n    z\n}\n";
+        let node = Parser::new( input ).parse( r_source_file );
+        assert_eq!( node.text(), input );
+
+        let errors = node.descendants( SyntaxKind::Error );
+        assert!( errors.is_empty(), "{:?}", errors );
+
+        let root = Root::cast( &node ).unwrap();
+        assert_eq!( root.source_file.as_ref().unwrap().items.len(), 4 );
+
+        //  The offside block inside `{ }` is closed by the less indented `z`.
+        let ifs = node.descendants( SyntaxKind::If );
+        let if_ = If::cast( ifs[ 0 ] ).unwrap();
+        assert_eq!( if_.then_branch.as_ref().unwrap().syntax().text().trim(), ":\n        y" );
+    }
+
+    #[ test ]
+    fn for_loops() {
+        let input = "fn f() {\n    for i in 0 .. n + 1 { i }\n    for ( k, ( a, b ) ) in pairs( xs ) {\n    }\n    for x in 1...10 { x }\n}\n";
+        let node = Parser::new( input ).parse( r_source_file );
+        assert_eq!( node.text(), input );
+
+        let fors = node.descendants( SyntaxKind::For );
+        let fors: Vec< For > = fors.iter().map( | node | For::cast( node ).unwrap() ).collect();
+        assert_eq!( fors.len(), 3 );
+
+        let Some( Pattern::BindingPattern( binding ) ) = fors[ 0 ].pattern.as_ref() else { panic!() };
+        assert_eq!( binding.name.as_ref().unwrap().id().unwrap().text, "i" );
+
+        //  `..` binds weaker than `+`.
+        let range = fors[ 0 ].iterable().unwrap();
+        assert_eq!( range.kind, SyntaxKind::InlineRange );
+        assert_eq!( range.text(), "0 .. n + 1 " );
+        assert!( !InlineRange::cast( range ).unwrap().is_inclusive() );
+
+        let Some( Pattern::TuplePattern( tuple ) ) = fors[ 1 ].pattern.as_ref() else { panic!() };
+        assert_eq!( tuple.patterns.len(), 2 );
+        let Pattern::TuplePattern( inner ) = &tuple.patterns[ 1 ] else { panic!() };
+        assert_eq!( inner.patterns.len(), 2 );
+        assert_eq!( fors[ 1 ].iterable().unwrap().kind, SyntaxKind::InlineCall );
+
+        assert!( InlineRange::cast( fors[ 2 ].iterable().unwrap() ).unwrap().is_inclusive() );
+        assert_eq!( fors[ 2 ].body.as_ref().unwrap().syntax().text().trim(), "{ x }" );
+    }
+
+    #[ test ]
+    fn jumps() {
+        let input = "fn f() {\n    'outer: for x in xs {\n        while x {\n            break 'outer x + 1\n            continue\n        }\n        continue 'outer\n    }\n    return\n    return x\n}\n";
+        let node = Parser::new( input ).parse( r_source_file );
+        assert_eq!( node.text(), input );
+
+        let fors = node.descendants( SyntaxKind::For );
+        let label = For::cast( fors[ 0 ] ).unwrap().label.unwrap();
+        assert_eq!( label.name(), Some( "outer" ) );
+
+        let whiles = node.descendants( SyntaxKind::While );
+        let while_ = While::cast( whiles[ 0 ] ).unwrap();
+        assert!( while_.label.is_none() );
+        assert!( while_.body.is_some() );
+
+        let breaks = node.descendants( SyntaxKind::Break );
+        let break_ = Break::cast( breaks[ 0 ] ).unwrap();
+        assert_eq!( break_.label().unwrap().text, "'outer" );
+        assert_eq!( break_.value().unwrap().kind, SyntaxKind::InlineBinary );
+
+        let continues = node.descendants( SyntaxKind::Continue );
+        let continues: Vec< Continue > = continues.iter().map( | node | Continue::cast( node ).unwrap() ).collect();
+        assert!( continues[ 0 ].label().is_none() );
+        assert_eq!( continues[ 1 ].label().unwrap().text, "'outer" );
+
+        //  Value has to be on the same line as `return`.
+        let returns = node.descendants( SyntaxKind::Return );
+        let values: Vec< Option< SyntaxKind > > = returns
+            .iter()
+            .map( | node | Return::cast( node ).unwrap().value().map( | x | x.kind ) )
+            .collect();
+        assert_eq!( values, vec![ None, Some( SyntaxKind::InlineVar ) ] );
+    }
+
+    #[ test ]
+    fn matches() {
+        let input = "\
+fn f() {
+    let y = match shape {
+        Circle( r ) if r > 0 => r * r,
+        Rect { w, h: 0, .. } => 0
+        Point { x, y } | Empty => { 1 }
+        ( _, -1, \"s\", true, null ) => 2,
+        other => return other
+    }
+}
+
+fn g():
+    match x:
+        1 | 2 => a
+        _ => b
+";
+        let node = Parser::new( input ).parse( r_source_file );
+        assert_eq!( node.text(), input );
+
+        let matches = node.descendants( SyntaxKind::Match );
+        assert_eq!( matches.len(), 2 );
+
+        let match_ = Match::cast( matches[ 0 ] ).unwrap();
+        assert_eq!( match_.scrutinee().unwrap().text().trim(), "shape" );
+        assert_eq!( match_.arms.len(), 5 );
+
+        let arm = &match_.arms[ 0 ];
+        let Some( Pattern::VariantPattern( variant ) ) = arm.pattern.as_ref() else { panic!() };
+        assert_eq!( variant.name.as_ref().unwrap().id().unwrap().text, "Circle" );
+        assert!( matches!( variant.patterns[ .. ], [ Pattern::BindingPattern( _ ) ] ) );
+        assert_eq!( arm.guard.as_ref().unwrap().condition().unwrap().kind, SyntaxKind::InlineBinary );
+        assert_eq!( arm.body().unwrap().text().trim(), "r * r" );
+
+        let Some( Pattern::RecordPattern( record ) ) = match_.arms[ 1 ].pattern.as_ref() else { panic!() };
+        assert!( record.has_rest() );
+        assert_eq!( record.fields.len(), 2 );
+        assert!( record.fields[ 0 ].pattern.is_none() );
+        assert!( matches!( record.fields[ 1 ].pattern, Some( Pattern::LiteralPattern( _ ) ) ) );
+
+        let Some( Pattern::OrPattern( or ) ) = match_.arms[ 2 ].pattern.as_ref() else { panic!() };
+        assert!( matches!( or.patterns[ .. ], [ Pattern::RecordPattern( _ ), Pattern::BindingPattern( _ ) ] ) );
+        assert_eq!( match_.arms[ 2 ].body().unwrap().kind, SyntaxKind::Block );
+
+        let Some( Pattern::TuplePattern( tuple ) ) = match_.arms[ 3 ].pattern.as_ref() else { panic!() };
+        let kinds: Vec< SyntaxKind > = tuple.patterns
+            .iter()
+            .map( | pattern | match pattern {
+                Pattern::LiteralPattern( literal ) => literal.literal().unwrap().kind,
+                pattern => pattern.syntax().kind,
+            } )
+            .collect();
+        assert_eq!( kinds, vec![
+            SyntaxKind::WildcardPattern,
+            SyntaxKind::InlineUnary,
+            SyntaxKind::String,
+            SyntaxKind::InlineBool,
+            SyntaxKind::InlineNull,
+        ] );
+
+        assert_eq!( match_.arms[ 4 ].body().unwrap().kind, SyntaxKind::Return );
+
+        let match_ = Match::cast( matches[ 1 ] ).unwrap();
+        assert_eq!( match_.arms.len(), 2 );
+        assert!( matches!( match_.arms[ 0 ].pattern, Some( Pattern::OrPattern( _ ) ) ) );
+        assert!( matches!( match_.arms[ 1 ].pattern, Some( Pattern::WildcardPattern( _ ) ) ) );
+    }
+
+    #[ test ]
+    fn closures() {
+        let input = "\
+fn f() {
+    g( |x: number, y| x * y, || a || b )
+    let h = | x | -> number { x * 2 }
+    -1
+    let k = fn ( x: number ) -> number {
+        x
+    }
+    k( fn ( x ):
+        x
+    )
+}
+";
+        let node = Parser::new( input ).parse( r_source_file );
+        assert_eq!( node.text(), input );
+
+        let closures = node.descendants( SyntaxKind::Closure );
+        let closures: Vec< Closure > = closures.iter().map( | node | Closure::cast( node ).unwrap() ).collect();
+        assert_eq!( closures.len(), 5 );
+
+        let params = &closures[ 0 ].params.as_ref().unwrap().params;
+        assert_eq!( params.len(), 2 );
+        assert_eq!( params[ 0 ].name().unwrap().text, "x" );
+        assert!( params[ 0 ].ty.is_some() );
+        assert!( params[ 1 ].ty.is_none() );
+        assert_eq!( closures[ 0 ].body().unwrap().text(), "x * y" );
+
+        //  `||` without parameters, the body is the whole `a || b`.
+        assert!( closures[ 1 ].params.as_ref().unwrap().params.is_empty() );
+        assert_eq!( closures[ 1 ].body().unwrap().kind, SyntaxKind::InlineBinary );
+        assert_eq!( closures[ 1 ].body().unwrap().text().trim(), "a || b" );
+
+        //  `-1` on the next line is a statement and not `{ x * 2 } - 1`.
+        assert!( closures[ 2 ].return_type.as_ref().unwrap().ty.is_some() );
+        assert_eq!( closures[ 2 ].body().unwrap().text(), "{ x * 2 }" );
+
+        assert!( closures[ 3 ].return_type.is_some() );
+        assert_eq!( closures[ 3 ].body().unwrap().kind, SyntaxKind::Block );
+        assert_eq!( closures[ 4 ].body().unwrap().kind, SyntaxKind::Block );
+
+        let blocks = node.descendants( SyntaxKind::Block );
+        let statements = blocks[ 0 ].children.iter().filter( | e | matches!( e, SyntaxElement::Node( _ ) ) ).count();
+        assert_eq!( statements, 5 );
+    }
+
+    #[ test ]
+    fn assignments() {
+        let input = "fn f() {\n    x = y = x + 1\n    p.x *= 2\n    ok ||= a || b\n}\n";
+        let node = Parser::new( input ).parse( r_source_file );
+        assert_eq!( node.text(), input );
+
+        let assigns = node.descendants( SyntaxKind::Assign );
+        let assigns: Vec< Assign > = assigns.iter().map( | node | Assign::cast( node ).unwrap() ).collect();
+
+        //  `x = ( y = x + 1 )`
+        assert_eq!( assigns.len(), 4 );
+        assert_eq!( assigns[ 0 ].target().unwrap().text().trim(), "x" );
+        assert_eq!( assigns[ 0 ].value().unwrap().kind, SyntaxKind::Assign );
+        assert_eq!( assigns[ 1 ].value().unwrap().kind, SyntaxKind::InlineBinary );
+        assert_eq!( assigns[ 0 ].op().unwrap().kind, TokenKind::Eq );
+        assert_eq!( assigns[ 0 ].binary_op(), None );
+
+        assert_eq!( assigns[ 2 ].target().unwrap().kind, SyntaxKind::InlineField );
+        assert_eq!( assigns[ 2 ].binary_op(), Some( TokenKind::Star ) );
+
+        assert_eq!( assigns[ 3 ].binary_op(), Some( TokenKind::PipePipe ) );
+        assert_eq!( assigns[ 3 ].value().unwrap().text().trim(), "a || b" );
+    }
+
+    #[ test ]
+    fn arrays_and_tuples() {
+        let input = "\
+fn f() {
+    let xs = [
+        1,
+        2 + 3,
+    ]
+    let zeros = [ 0; n * 2 ]
+    let t = ( a, ( b, c ), )
+    let u = ()
+    let v = ( 1 )
+    xs[ i + 1 ].y[ 0 ] + t.1.0
+}
+";
+        let node = Parser::new( input ).parse( r_source_file );
+        assert_eq!( node.text(), input );
+
+        let arrays = node.descendants( SyntaxKind::InlineArray );
+        let elements = InlineArray::cast( arrays[ 0 ] ).unwrap().elements();
+        assert_eq!( elements.len(), 2 );
+        assert_eq!( elements[ 1 ].kind, SyntaxKind::InlineBinary );
+
+        let repeats = node.descendants( SyntaxKind::InlineArrayRepeat );
+        let repeat = InlineArrayRepeat::cast( repeats[ 0 ] ).unwrap();
+        assert_eq!( repeat.value().unwrap().kind, SyntaxKind::InlineNumber );
+        assert_eq!( repeat.count().unwrap().text().trim(), "n * 2" );
+
+        let tuples = node.descendants( SyntaxKind::InlineTuple );
+        let lens: Vec< usize > = tuples.iter().map( | node | InlineTuple::cast( node ).unwrap().elements().len() ).collect();
+        assert_eq!( lens, vec![ 2, 2, 0 ] );
+
+        let subexprs = node.descendants( SyntaxKind::InlineSubexpr );
+        assert_eq!( subexprs.len(), 1 );
+
+        //  `xs[ i + 1 ].y[ 0 ]`
+        let indexes = node.descendants( SyntaxKind::InlineIndex );
+        let outer = InlineIndex::cast( indexes[ 0 ] ).unwrap();
+        assert_eq!( outer.base().unwrap().kind, SyntaxKind::InlineField );
+        assert_eq!( outer.index().unwrap().kind, SyntaxKind::InlineNumber );
+        let inner = InlineIndex::cast( indexes[ 1 ] ).unwrap();
+        assert_eq!( inner.index().unwrap().text().trim(), "i + 1" );
+
+        //  `t.1.0` is `( t.1 ).0`.
+        let fields = node.descendants( SyntaxKind::InlineTupleField );
+        let fields: Vec< InlineTupleField > = fields.iter().map( | node | InlineTupleField::cast( node ).unwrap() ).collect();
+        assert_eq!( fields[ 0 ].index(), Some( 0 ) );
+        assert_eq!( fields[ 0 ].base().unwrap().kind, SyntaxKind::InlineTupleField );
+        assert_eq!( fields[ 1 ].index(), Some( 1 ) );
+    }
+
+    #[ test ]
+    fn struct_literals() {
+        let input = "\
+fn f() {
+    let p = Point { x: 1, y }
+    let q = Shape.Rect {
+        w: 2,
+        ..p
+    }
+    if p == Point { x }
+    if ( p == Point { x, y } ) { 2 }
+    while f( Point {} ) { 3 }
+    for x in xs { x }
+    match p { Point { x, .. } => Point { x } }
+}
+";
+        let node = Parser::new( input ).parse( r_source_file );
+        assert_eq!( node.text(), input );
+
+        let structs = node.descendants( SyntaxKind::InlineStruct );
+        let structs: Vec< InlineStruct > = structs.iter().map( | node | InlineStruct::cast( node ).unwrap() ).collect();
+
+        let texts: Vec< std::string::String > = structs.iter().map( | x | x.syntax().text().trim().to_string() ).collect();
+        assert_eq!( texts, vec![
+            "Point { x: 1, y }",
+            "Shape.Rect {\n        w: 2,\n        ..p\n    }",
+            "Point { x, y }",
+            "Point {}",
+            "Point { x }",
+        ] );
+
+        assert_eq!( structs[ 0 ].path().unwrap().kind, SyntaxKind::InlineVar );
+        assert_eq!( structs[ 0 ].fields.len(), 2 );
+        assert_eq!( structs[ 0 ].fields[ 0 ].value().unwrap().kind, SyntaxKind::InlineNumber );
+        assert!( structs[ 0 ].fields[ 1 ].value().is_none() );
+
+        assert_eq!( structs[ 1 ].path().unwrap().kind, SyntaxKind::InlineField );
+        assert_eq!( structs[ 1 ].spread.as_ref().unwrap().value().unwrap().text().trim(), "p" );
+
+        //  `{ x }` after `if p == Point` is the block.
+        let ifs = node.descendants( SyntaxKind::If );
+        assert_eq!( If::cast( ifs[ 0 ] ).unwrap().then_branch.as_ref().unwrap().syntax().text().trim(), "{ x }" );
+        assert_eq!( If::cast( ifs[ 1 ] ).unwrap().then_branch.as_ref().unwrap().syntax().text().trim(), "{ 2 }" );
+    }
+
+    #[ test ]
+    fn struct_methods() {
+        let input = "struct Point {\n    x: number,\n    fn new( x: number ) -> Point { Point { x } }\n    fn len( &self ) -> number { self.x }\n    fn moved( self, dx: number ) -> Point { Point.new( self.x + dx ) }\n}\n";
+        let node = Parser::new( input ).parse( r_source_file );
+        assert_eq!( node.text(), input );
+
+        let structs = node.descendants( SyntaxKind::Struct );
+        let items = Struct::cast( structs[ 0 ] ).unwrap().items.unwrap();
+
+        assert_eq!( items.fields.len(), 1 );
+        assert_eq!( items.fields[ 0 ].name().unwrap().text, "x" );
+
+        let methods: Vec< ( &str, bool, Option< bool >, usize ) > = items.methods
+            .iter()
+            .map( | m | (
+                m.name.as_ref().unwrap().id().unwrap().text,
+                m.is_static(),
+                m.receiver().map( | x | x.is_ref() ),
+                m.params.as_ref().unwrap().params.len(),
+            ) )
+            .collect();
+        assert_eq!( methods, vec![
+            ( "new", true, None, 1 ),
+            ( "len", false, Some( true ), 0 ),
+            ( "moved", false, Some( false ), 1 ),
+        ] );
+
+        let calls = node.descendants( SyntaxKind::InlineMethodCall );
+        let call = InlineMethodCall::cast( calls[ 0 ] ).unwrap();
+        assert_eq!( call.name().unwrap().text, "new" );
+        assert_eq!( call.receiver().unwrap().kind, SyntaxKind::InlineVar );
+        assert_eq!( call.args.as_ref().unwrap().args[ 0 ].value().unwrap().kind, SyntaxKind::InlineBinary );
+    }
+
+    #[ test ]
+    fn traits() {
+        let input = "trait Show< T > {\n    fn show( &self, x: T ) -> string;\n    fn print( &self ) { print( self.show() ) }\n}\n\nimpl Show< number > for Point {\n    fn show( &self, x: number ) -> string { \"\" }\n}\n";
+        let node = Parser::new( input ).parse( r_source_file );
+        assert_eq!( node.text(), input );
+
+        let root = Root::cast( &node ).unwrap();
+        let items = &root.source_file.as_ref().unwrap().items;
+
+        let Item::Trait( t ) = &items[ 0 ] else { panic!() };
+        assert_eq!( t.name.as_ref().unwrap().id().unwrap().text, "Show" );
+        assert_eq!( t.generic_params.as_ref().unwrap().params.len(), 1 );
+        let methods: Vec< ( &str, bool ) > = t.items.as_ref().unwrap().methods
+            .iter()
+            .map( | m | ( m.name.as_ref().unwrap().id().unwrap().text, m.has_default() ) )
+            .collect();
+        assert_eq!( methods, vec![ ( "show", false ), ( "print", true ) ] );
+        assert!( t.items.as_ref().unwrap().methods[ 0 ].receiver().unwrap().is_ref() );
+
+        let Item::Impl( i ) = &items[ 1 ] else { panic!() };
+        let trait_ref = i.trait_ref.as_ref().unwrap();
+        assert_eq!( trait_ref.name().unwrap().text, "Show" );
+        assert_eq!( trait_ref.generic_args.as_ref().unwrap().args[ 0 ].syntax().text().trim(), "number" );
+        assert_eq!( i.target.as_ref().unwrap().syntax().text().trim(), "Point" );
+        assert_eq!( i.items.as_ref().unwrap().methods.len(), 1 );
+    }
+
+    #[ test ]
+    fn imports() {
+        let input = "import { add, Point as P } from \"./math\"\n\n/// Docs.\npub fn f() {}\nexport const N = 1\nstruct S {}\n";
+        let node = Parser::new( input ).parse( r_source_file );
+        assert_eq!( node.text(), input );
+
+        let root = Root::cast( &node ).unwrap();
+        let items = &root.source_file.as_ref().unwrap().items;
+
+        let Item::Import( import ) = &items[ 0 ] else { panic!() };
+        assert_eq!( import.path_value().as_deref(), Some( "./math" ) );
+        let names: Vec< ( &str, &str ) > = import.names
+            .iter()
+            .map( | x | ( x.name().unwrap().text, x.local_name().unwrap().text ) )
+            .collect();
+        assert_eq!( names, vec![ ( "add", "add" ), ( "Point", "P" ) ] );
+
+        let exported: Vec< bool > = items.iter().map( | x | x.is_exported() ).collect();
+        assert_eq!( exported, vec![ false, true, true, false ] );
+
+        let Item::Fn( f ) = &items[ 1 ] else { panic!() };
+        assert_eq!( f.docs().as_deref(), Some( "Docs." ) );
+    }
+
 }
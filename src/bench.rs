@@ -0,0 +1,132 @@
+//  Lexer benchmarks over large synthetic sources:
+//
+//      cargo test --release bench -- --ignored --nocapture
+//
+
+use std::fmt::Write;
+use std::hint::black_box;
+use std::time::{ Duration, Instant };
+
+use crate::{ Lexer, Token, TokenBuffer };
+
+//  Generates a source with `n_items` functions and structs in the style of generated code:
+//  comments, strings with interpolations, numbers and indented blocks.
+//
+pub fn synthetic_source( n_items: usize ) -> String {
+    let mut s = String::new();
+
+    for i in 0 .. n_items {
+        let _ = write!( s, "\
+/// Computes value number {i}.
+fn compute_{i}( x: number, y: number ) -> number {{
+    // Plain comment with some text in it.
+    let a = x * {i} + y / 2.5e3 - 0x{i:x}_u32
+    let s = \"item {i}: ${{ a + 1 }} of \\\"${{ y }}\\\"\\n\"
+    /* block /* nested */ comment */
+    if a >= 100 && y != 0 {{
+        log( s, a, [ 1, 2, 3 ] )
+    }}
+    a
+}}
+
+struct Point{i} {{
+    x: number,
+    y: number,
+}}
+
+fn area_{i}( p: Point{i} ):
+    let t = \"\"\"
+        multi-line ${{ p.x }}
+        text
+        \"\"\"
+    p.x * p.y
+
+" );
+    }
+
+    s
+}
+
+struct Stats {
+    n_bytes: usize,
+    n_tokens: usize,
+    elapsed: Duration,
+}
+
+impl Stats {
+
+    fn print( &self, name: &str ) {
+        let secs = self.elapsed.as_secs_f64();
+        println!(
+            "{:<24} {:>10} bytes {:>10} tokens {:>10.2?} {:>8.2} Mtokens/s {:>8.2} MB/s",
+            name,
+            self.n_bytes,
+            self.n_tokens,
+            self.elapsed,
+            self.n_tokens as f64 / secs / 1e6,
+            self.n_bytes as f64 / secs / 1e6,
+        );
+    }
+
+}
+
+//  Best of `n_runs`.
+//
+fn measure( input: &str, n_runs: usize, f: impl Fn( &str ) -> usize ) -> Stats {
+    let mut elapsed = Duration::MAX;
+    let mut n_tokens = 0;
+
+    for _ in 0 .. n_runs {
+        let start = Instant::now();
+        n_tokens = black_box( f( black_box( input ) ) );
+        elapsed = elapsed.min( start.elapsed() );
+    }
+
+    Stats {
+        n_bytes: input.len(),
+        n_tokens,
+        elapsed,
+    }
+}
+
+fn run_benches( n_items: usize ) {
+    let input = synthetic_source( n_items );
+
+    measure( &input, 10, | input | Lexer::with_indents( input ).count() )
+        .print( "lexer" );
+
+    measure( &input, 10, | input | Lexer::with_indents( input ).collect::< Vec< Token > >().len() )
+        .print( "lexer -> Vec< Token >" );
+
+    measure( &input, 10, | input | TokenBuffer::new( &mut Lexer::with_indents( input ) ).len() )
+        .print( "lexer -> TokenBuffer" );
+}
+
+#[ cfg( test ) ]
+mod tests {
+    use super::*;
+
+    #[ test ]
+    fn synthetic_source_is_valid() {
+        let input = synthetic_source( 3 );
+
+        let mut lexer = Lexer::with_indents( &input );
+        let tokens: Vec< Token > = lexer.by_ref().collect();
+
+        assert!( lexer.errors().is_empty(), "{:?}", lexer.errors() );
+        assert!( tokens.iter().all( | t | t.kind != crate::TokenKind::Unknown ) );
+    }
+
+    #[ test ]
+    #[ ignore ]
+    fn bench_lexer_1mb() {
+        run_benches( 2_000 );
+    }
+
+    #[ test ]
+    #[ ignore ]
+    fn bench_lexer_10mb() {
+        run_benches( 20_000 );
+    }
+
+}
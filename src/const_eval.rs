@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{ SyntaxNode, SyntaxKind, SyntaxElement, TokenKind, AstNode };
+use crate::ast;
+use crate::number::{ NumberError, NumberValue };
+
+//  Compile-time evaluation of `const` items and array sizes:
+//
+//      const N = 4
+//      const SIZE = N * 2 + 1
+//      type Buffer = [ number; SIZE ]
+//
+//  Backends get the computed values and inline them instead of the expressions.
+//
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub enum ConstValue {
+    Int( i64 ),
+    Float( f64 ),
+    Bool( bool ),
+    String( String ),
+    Null,
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+#[ derive( Clone, PartialEq ) ]
+pub struct ConstError {
+    pub start: usize,
+    pub end: usize,
+    pub kind: ConstErrorKind,
+}
+
+impl fmt::Debug for ConstError {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        write!( f , "ERROR: {:?} at {}..{}", self.kind, self.start, self.end )
+    }
+
+}
+
+#[ derive( Clone, PartialEq ) ]
+pub enum ConstErrorKind {
+    NotConst,
+    UnknownConst( String ),
+    Cycle( String ),
+    InvalidNumber( NumberError ),
+    InvalidOperands,
+    Overflow,
+    DivisionByZero,
+    InvalidArraySize,
+}
+
+impl fmt::Debug for ConstErrorKind {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match self {
+            Self::NotConst => write!( f, "Expression can't be evaluated at compile time" ),
+            Self::UnknownConst( name ) => write!( f, "Unknown constant {}", name ),
+            Self::Cycle( name ) => write!( f, "Constant {} depends on itself", name ),
+            Self::InvalidNumber( error ) => write!( f, "{:?}", error ),
+            Self::InvalidOperands => write!( f, "Invalid operands" ),
+            Self::Overflow => write!( f, "Overflow in constant expression" ),
+            Self::DivisionByZero => write!( f, "Division by zero in constant expression" ),
+            Self::InvalidArraySize => write!( f, "Array size must be a non-negative integer" ),
+        }
+    }
+
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+pub struct ConstEvaluator< 'a > {
+    //  Initializers of `const` items.
+    items: HashMap< &'a str, &'a SyntaxNode< 'a > >,
+    values: HashMap< &'a str, Result< ConstValue, ConstError > >,
+    //  Constants being evaluated, innermost last.
+    stack: Vec< &'a str >,
+}
+
+impl < 'a > ConstEvaluator< 'a > {
+
+    pub fn new( root: &'a SyntaxNode< 'a > ) -> Self {
+        let mut items = HashMap::new();
+        collect_consts( root, &mut items );
+
+        ConstEvaluator {
+            items,
+            values: HashMap::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    //  Value of the `const` item `name`, computed once.
+    //
+    pub fn value( &mut self, name: &str ) -> Option< Result< ConstValue, ConstError > > {
+        if let Some( value ) = self.values.get( name ) {
+            return Some( value.clone() );
+        }
+
+        let ( &name, &expr ) = self.items.get_key_value( name )?;
+
+        self.stack.push( name );
+        let value = self.eval( expr );
+        self.stack.pop();
+
+        self.values.insert( name, value.clone() );
+
+        Some( value )
+    }
+
+    //  Constant defined outside of the tree, e.g. imported from another module.
+    //
+    pub fn define( &mut self, name: &'a str, value: ConstValue ) {
+        self.values.insert( name, Ok( value ) );
+    }
+
+    //  Size of a `[ T; N ]` array type, `node` is `TypeArray`.
+    //
+    pub fn array_size( &mut self, node: &'a SyntaxNode< 'a > ) -> Result< usize, ConstError > {
+        let size = node.nodes().nth( 1 ).ok_or_else( || error( node, ConstErrorKind::InvalidArraySize ) )?;
+
+        match self.eval( size )? {
+            ConstValue::Int( n ) => usize::try_from( n ).map_err( | _ | error( size, ConstErrorKind::InvalidArraySize ) ),
+            _ => Err( error( size, ConstErrorKind::InvalidArraySize ) ),
+        }
+    }
+
+    //  Evaluates all `const` items and array sizes of the tree, returns the errors.
+    //
+    pub fn check( &mut self, root: &'a SyntaxNode< 'a > ) -> Vec< ConstError > {
+        let mut errors = Vec::new();
+
+        let mut names: Vec< &'a str > = self.items.keys().copied().collect();
+        names.sort_by_key( | name | self.items[ name ].range() );
+        for name in names {
+            if let Some( Err( e ) ) = self.value( name ) {
+                //  Invalid numbers are reported by `validation::validate`.
+                if !errors.contains( &e ) && !matches!( e.kind, ConstErrorKind::InvalidNumber( _ ) ) {
+                    errors.push( e );
+                }
+            }
+        }
+
+        let arrays = root.descendants( SyntaxKind::TypeArray );
+        for array in arrays {
+            if let Err( e ) = self.array_size( array ) {
+                if !errors.contains( &e ) && !matches!( e.kind, ConstErrorKind::InvalidNumber( _ ) ) {
+                    errors.push( e );
+                }
+            }
+        }
+
+        errors
+    }
+
+    pub fn eval( &mut self, expr: &'a SyntaxNode< 'a > ) -> Result< ConstValue, ConstError > {
+        match expr.kind {
+            SyntaxKind::InlineNumber => self.eval_number( expr, expr, false ),
+
+            SyntaxKind::InlineBool => Ok( ConstValue::Bool( ast::InlineBool::cast( expr ).unwrap().value() ) ),
+
+            SyntaxKind::InlineNull => Ok( ConstValue::Null ),
+
+            SyntaxKind::String => match ast::String::cast( expr ).unwrap().value() {
+                Some( value ) => Ok( ConstValue::String( value.into_owned() ) ),
+                //  Interpolations.
+                None => Err( error( expr, ConstErrorKind::NotConst ) ),
+            },
+
+            SyntaxKind::InlineSubexpr => match expr.nodes().next() {
+                Some( inner ) => self.eval( inner ),
+                None => Err( error( expr, ConstErrorKind::NotConst ) ),
+            },
+
+            SyntaxKind::InlineVar => {
+                let name = expr.find_token( TokenKind::Id ).map_or( "", | token | token.text );
+
+                if self.stack.contains( &name ) {
+                    return Err( error( expr, ConstErrorKind::Cycle( name.to_string() ) ) );
+                }
+
+                match self.value( name ) {
+                    Some( value ) => value,
+                    None => Err( error( expr, ConstErrorKind::UnknownConst( name.to_string() ) ) ),
+                }
+            }
+
+            SyntaxKind::InlineUnary => self.eval_unary( expr ),
+
+            SyntaxKind::InlineBinary => self.eval_binary( expr ),
+
+            _ => Err( error( expr, ConstErrorKind::NotConst ) ),
+        }
+    }
+
+    //  Errors are reported at `expr`, the literal itself or the negation of it.
+    //
+    fn eval_number( &self, expr: &SyntaxNode, number: &'a SyntaxNode< 'a >, negative: bool ) -> Result< ConstValue, ConstError > {
+        let number = ast::InlineNumber::cast( number ).unwrap().signed_value( negative ).map_err( | e | error( expr, ConstErrorKind::InvalidNumber( e ) ) )?;
+
+        match number.value {
+            NumberValue::Int( n ) if negative => 0_i64
+                .checked_sub_unsigned( n )
+                .map( ConstValue::Int )
+                .ok_or_else( || error( expr, ConstErrorKind::Overflow ) ),
+            NumberValue::Int( n ) => i64::try_from( n )
+                .map( ConstValue::Int )
+                .map_err( | _ | error( expr, ConstErrorKind::Overflow ) ),
+            NumberValue::Float( x ) if negative => Ok( ConstValue::Float( -x ) ),
+            NumberValue::Float( x ) => Ok( ConstValue::Float( x ) ),
+        }
+    }
+
+    fn eval_unary( &mut self, expr: &'a SyntaxNode< 'a > ) -> Result< ConstValue, ConstError > {
+        let op = first_token( expr ).ok_or_else( || error( expr, ConstErrorKind::NotConst ) )?;
+        let operand = expr.nodes().next().ok_or_else( || error( expr, ConstErrorKind::NotConst ) )?;
+
+        //  `-9223372036854775808` is `i64::MIN`, but `9223372036854775808` doesn't fit into `i64`.
+        if op == TokenKind::Minus && operand.kind == SyntaxKind::InlineNumber {
+            return self.eval_number( expr, operand, true );
+        }
+
+        match ( op, self.eval( operand )? ) {
+            ( TokenKind::Plus, value @ ( ConstValue::Int( _ ) | ConstValue::Float( _ ) ) ) => Ok( value ),
+            ( TokenKind::Minus, ConstValue::Int( n ) ) => n.checked_neg().map( ConstValue::Int ).ok_or_else( || error( expr, ConstErrorKind::Overflow ) ),
+            ( TokenKind::Minus, ConstValue::Float( x ) ) => Ok( ConstValue::Float( -x ) ),
+            ( TokenKind::Bang, ConstValue::Bool( b ) ) => Ok( ConstValue::Bool( !b ) ),
+            _ => Err( error( expr, ConstErrorKind::InvalidOperands ) ),
+        }
+    }
+
+    fn eval_binary( &mut self, expr: &'a SyntaxNode< 'a > ) -> Result< ConstValue, ConstError > {
+        let mut operands = expr.nodes();
+        let ( Some( left ), Some( right ) ) = ( operands.next(), operands.next() ) else {
+            return Err( error( expr, ConstErrorKind::NotConst ) );
+        };
+        let op = expr.children
+            .iter()
+            .find_map( | e | match e {
+                SyntaxElement::Token( token ) if !matches!( token.kind, TokenKind::Space | TokenKind::BlockComment ) => Some( token.kind ),
+                _ => None,
+            } )
+            .ok_or_else( || error( expr, ConstErrorKind::NotConst ) )?;
+
+        let left = self.eval( left )?;
+
+        //  Short-circuit, `false && x` is `false` whatever `x` is.
+        match ( op, &left ) {
+            ( TokenKind::AmpAmp, ConstValue::Bool( false ) ) => return Ok( left ),
+            ( TokenKind::PipePipe, ConstValue::Bool( true ) ) => return Ok( left ),
+            _ => {},
+        }
+
+        let right = self.eval( right )?;
+
+        let overflow = || error( expr, ConstErrorKind::Overflow );
+        let invalid = || error( expr, ConstErrorKind::InvalidOperands );
+
+        use ConstValue::*;
+
+        let value = match ( left, right ) {
+            ( Int( a ), Int( b ) ) => match op {
+                TokenKind::Plus => Int( a.checked_add( b ).ok_or_else( overflow )? ),
+                TokenKind::Minus => Int( a.checked_sub( b ).ok_or_else( overflow )? ),
+                TokenKind::Star => Int( a.checked_mul( b ).ok_or_else( overflow )? ),
+                TokenKind::Slash | TokenKind::Percent if b == 0 => return Err( error( expr, ConstErrorKind::DivisionByZero ) ),
+                TokenKind::Slash => Int( a.checked_div( b ).ok_or_else( overflow )? ),
+                TokenKind::Percent => Int( a.checked_rem( b ).ok_or_else( overflow )? ),
+                _ => compare( op, a.partial_cmp( &b ) ).ok_or_else( invalid )?,
+            },
+
+            //  Integers are promoted to floats.
+            ( a @ ( Int( _ ) | Float( _ ) ), b @ ( Int( _ ) | Float( _ ) ) ) => {
+                let ( a, b ) = ( as_float( a ), as_float( b ) );
+
+                match op {
+                    TokenKind::Plus => Float( a + b ),
+                    TokenKind::Minus => Float( a - b ),
+                    TokenKind::Star => Float( a * b ),
+                    TokenKind::Slash => Float( a / b ),
+                    TokenKind::Percent => Float( a % b ),
+                    _ => compare( op, a.partial_cmp( &b ) ).ok_or_else( invalid )?,
+                }
+            }
+
+            ( Bool( a ), Bool( b ) ) => match op {
+                TokenKind::AmpAmp => Bool( a && b ),
+                TokenKind::PipePipe => Bool( a || b ),
+                TokenKind::EqEq => Bool( a == b ),
+                TokenKind::BangEq => Bool( a != b ),
+                _ => return Err( invalid() ),
+            },
+
+            ( String( a ), String( b ) ) => match op {
+                TokenKind::Plus => String( a + &b ),
+                _ => compare( op, a.partial_cmp( &b ) ).ok_or_else( invalid )?,
+            },
+
+            ( a, b ) => match op {
+                TokenKind::EqEq => Bool( a == b ),
+                TokenKind::BangEq => Bool( a != b ),
+                _ => return Err( invalid() ),
+            },
+        };
+
+        Ok( value )
+    }
+
+}
+
+fn as_float( value: ConstValue ) -> f64 {
+    match value {
+        ConstValue::Int( n ) => n as f64,
+        ConstValue::Float( x ) => x,
+        _ => unreachable!(),
+    }
+}
+
+fn compare( op: TokenKind, ordering: Option< std::cmp::Ordering > ) -> Option< ConstValue > {
+    use std::cmp::Ordering::*;
+
+    let ordering = ordering?;
+    let value = match op {
+        TokenKind::EqEq => ordering == Equal,
+        TokenKind::BangEq => ordering != Equal,
+        TokenKind::Lt => ordering == Less,
+        TokenKind::LtEq => ordering != Greater,
+        TokenKind::Gt => ordering == Greater,
+        TokenKind::GtEq => ordering != Less,
+        _ => return None,
+    };
+
+    Some( ConstValue::Bool( value ) )
+}
+
+fn error( node: &SyntaxNode, kind: ConstErrorKind ) -> ConstError {
+    let ( start, end ) = node.range().unwrap_or( ( 0, 0 ) );
+
+    ConstError {
+        start,
+        end,
+        kind,
+    }
+}
+
+fn first_token( node: &SyntaxNode ) -> Option< TokenKind > {
+    node.children.iter().find_map( | e | match e {
+        SyntaxElement::Token( token ) => Some( token.kind ),
+        _ => None,
+    } )
+}
+
+fn collect_consts< 'a >( node: &'a SyntaxNode< 'a >, items: &mut HashMap< &'a str, &'a SyntaxNode< 'a > > ) {
+    for child in node.nodes() {
+        if child.kind == SyntaxKind::Const {
+            let item = ast::Const::cast( child ).unwrap();
+            //  The first one wins, redefinitions are reported by name resolution.
+            if let ( Some( name ), Some( value ) ) = ( child.name(), item.value() ) {
+                items.entry( name ).or_insert( value );
+            }
+
+        } else {
+            collect_consts( child, items );
+        }
+    }
+}
+
+#[ cfg( test ) ]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::grammar::r_source_file;
+
+    #[ test ]
+    fn consts() {
+        let input = "\
+const N = 4
+const SIZE: number = N * 2 + 1
+const HALF = SIZE / 2.0
+const NAME = \"buf\" + \"fer\"
+const BIG = N > 3 && !false
+const NEG = -( N - 10 ) % 4
+const MIN = -9223372036854775808
+const MIN_I8 = -128i8
+type Buffer = [ number; SIZE ]
+";
+        let ( node, errors, lexer_errors ) = Parser::new( input ).parse_with_errors( r_source_file );
+        assert!( errors.is_empty() && lexer_errors.is_empty(), "{:?} {:?}", errors, lexer_errors );
+
+        let mut eval = ConstEvaluator::new( &node );
+
+        assert_eq!( eval.value( "N" ), Some( Ok( ConstValue::Int( 4 ) ) ) );
+        assert_eq!( eval.value( "SIZE" ), Some( Ok( ConstValue::Int( 9 ) ) ) );
+        assert_eq!( eval.value( "HALF" ), Some( Ok( ConstValue::Float( 4.5 ) ) ) );
+        assert_eq!( eval.value( "NAME" ), Some( Ok( ConstValue::String( "buffer".to_string() ) ) ) );
+        assert_eq!( eval.value( "BIG" ), Some( Ok( ConstValue::Bool( true ) ) ) );
+        assert_eq!( eval.value( "NEG" ), Some( Ok( ConstValue::Int( 2 ) ) ) );
+        assert_eq!( eval.value( "MIN" ), Some( Ok( ConstValue::Int( i64::MIN ) ) ) );
+        assert_eq!( eval.value( "MIN_I8" ), Some( Ok( ConstValue::Int( -128 ) ) ) );
+        assert_eq!( eval.value( "X" ), None );
+
+        let arrays = node.descendants( SyntaxKind::TypeArray );
+        assert_eq!( eval.array_size( arrays[ 0 ] ), Ok( 9 ) );
+
+        assert!( eval.check( &node ).is_empty() );
+    }
+
+    #[ test ]
+    fn errors() {
+        let input = "\
+const A = B + 1
+const B = A
+const C = 1 / ( 2 - 2 )
+const D = f( 1 )
+const E = X
+const F = true + 1
+const G = 9223372036854775807 + 1
+const H = -9223372036854775809
+type T = [ number; -1 ]
+type U = [ number; 1.5 ]
+";
+        let ( node, errors, lexer_errors ) = Parser::new( input ).parse_with_errors( r_source_file );
+        assert!( errors.is_empty() && lexer_errors.is_empty(), "{:?} {:?}", errors, lexer_errors );
+
+        let errors: Vec< ( ConstErrorKind, &str ) > = ConstEvaluator::new( &node )
+            .check( &node )
+            .into_iter()
+            .map( | e | ( e.kind, &input[ e.start .. e.end ] ) )
+            .collect();
+
+        assert_eq!( errors, vec![
+            ( ConstErrorKind::Cycle( "A".to_string() ), "A" ),
+            ( ConstErrorKind::DivisionByZero, "1 / ( 2 - 2 )" ),
+            ( ConstErrorKind::NotConst, "f( 1 )" ),
+            ( ConstErrorKind::UnknownConst( "X".to_string() ), "X" ),
+            ( ConstErrorKind::InvalidOperands, "true + 1" ),
+            ( ConstErrorKind::Overflow, "9223372036854775807 + 1" ),
+            ( ConstErrorKind::Overflow, "-9223372036854775809" ),
+            ( ConstErrorKind::InvalidArraySize, "-1" ),
+            ( ConstErrorKind::InvalidArraySize, "1.5" ),
+        ] );
+    }
+
+}
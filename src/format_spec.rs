@@ -0,0 +1,274 @@
+use std::fmt;
+
+//  Format specifier of a string interpolation, the part after `:` in `${ x:>10.2 }`:
+//
+//      [[fill]align][sign]['#']['0'][width]['.' precision][kind]
+//
+//      ${ price:.2 }  ${ name:>10 }  ${ name:*^10 }  ${ x:+08.3e }  ${ x:#hex }
+//
+
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+pub enum Sign {
+    Plus,
+    Minus,
+}
+
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+pub enum FormatKind {
+    Hex,
+    UpperHex,
+    Octal,
+    Binary,
+    Exp,
+    UpperExp,
+}
+
+impl FormatKind {
+
+    fn from_str( s: &str ) -> Option< Self > {
+        match s {
+            "x" | "hex" => Some( Self::Hex ),
+            "X" | "HEX" => Some( Self::UpperHex ),
+            "o" | "oct" => Some( Self::Octal ),
+            "b" | "bin" => Some( Self::Binary ),
+            "e" | "exp" => Some( Self::Exp ),
+            "E" | "EXP" => Some( Self::UpperExp ),
+            _ => None,
+        }
+    }
+
+    fn is_integer( self ) -> bool {
+        matches!( self, Self::Hex | Self::UpperHex | Self::Octal | Self::Binary )
+    }
+
+}
+
+#[ derive( Debug, Clone, Copy, PartialEq, Default ) ]
+pub struct FormatSpec {
+    pub fill: Option< char >,
+    pub align: Option< Align >,
+    pub sign: Option< Sign >,
+    pub alternate: bool,
+    pub zero: bool,
+    pub width: Option< usize >,
+    pub precision: Option< usize >,
+    pub kind: Option< FormatKind >,
+}
+
+//  Type of a formatted value, as far as it is known.
+//
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+pub enum ValueKind {
+    Int,
+    Float,
+    //  `number`, an integer or a float.
+    Number,
+    String,
+    Bool,
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+#[ derive( Clone, PartialEq ) ]
+pub enum FormatSpecError {
+    InvalidKind( String ),
+    MissingPrecision,
+    TooLarge,
+    NotApplicable( &'static str, ValueKind ),
+}
+
+impl fmt::Debug for FormatSpecError {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match self {
+            Self::InvalidKind( kind ) => write!( f, "Invalid format {:?}", kind ),
+            Self::MissingPrecision => write!( f, "Precision is required after `.`" ),
+            Self::TooLarge => write!( f, "Format width or precision is too large" ),
+            Self::NotApplicable( what, kind ) => write!( f, "Format {} is not applicable to {:?}", what, kind ),
+        }
+    }
+
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+//  `text` is the spec without the leading `:`.
+//
+pub fn parse_format_spec( text: &str ) -> Result< FormatSpec, FormatSpecError > {
+    let mut spec = FormatSpec::default();
+
+    let align = | c: char | match c {
+        '<' => Some( Align::Left ),
+        '^' => Some( Align::Center ),
+        '>' => Some( Align::Right ),
+        _ => None,
+    };
+
+    let mut chars = text.chars();
+    let mut s = text;
+    match ( chars.next(), chars.next().and_then( align ) ) {
+        ( Some( fill ), Some( a ) ) => {
+            spec.fill = Some( fill );
+            spec.align = Some( a );
+            s = chars.as_str();
+        }
+        ( Some( c ), _ ) if align( c ).is_some() => {
+            spec.align = align( c );
+            s = &s[ 1 .. ];
+        }
+        _ => {},
+    }
+
+    if let Some( rest ) = s.strip_prefix( '+' ) {
+        spec.sign = Some( Sign::Plus );
+        s = rest;
+    } else if let Some( rest ) = s.strip_prefix( '-' ) {
+        spec.sign = Some( Sign::Minus );
+        s = rest;
+    }
+
+    if let Some( rest ) = s.strip_prefix( '#' ) {
+        spec.alternate = true;
+        s = rest;
+    }
+
+    //  `0` before width is a flag, a single `0` is a flag too.
+    if let Some( rest ) = s.strip_prefix( '0' ) {
+        spec.zero = true;
+        s = rest;
+    }
+
+    let ( width, rest ) = split_digits( s )?;
+    spec.width = width;
+    s = rest;
+
+    if let Some( rest ) = s.strip_prefix( '.' ) {
+        let ( precision, rest ) = split_digits( rest )?;
+        if precision.is_none() {
+            return Err( FormatSpecError::MissingPrecision );
+        }
+        spec.precision = precision;
+        s = rest;
+    }
+
+    if !s.is_empty() {
+        match FormatKind::from_str( s ) {
+            Some( kind ) => spec.kind = Some( kind ),
+            None => return Err( FormatSpecError::InvalidKind( s.to_string() ) ),
+        }
+    }
+
+    Ok( spec )
+}
+
+fn split_digits( s: &str ) -> Result< ( Option< usize >, &str ), FormatSpecError > {
+    let end = s.find( | c: char | !c.is_ascii_digit() ).unwrap_or( s.len() );
+    if end == 0 {
+        return Ok( ( None, s ) );
+    }
+
+    let value = s[ .. end ].parse().map_err( | _ | FormatSpecError::TooLarge )?;
+
+    Ok( ( Some( value ), &s[ end .. ] ) )
+}
+
+impl FormatSpec {
+
+    //  Checks that the spec makes sense for a value of the `kind` type.
+    //
+    pub fn check( &self, kind: ValueKind ) -> Result< (), FormatSpecError > {
+        let is_number = matches!( kind, ValueKind::Int | ValueKind::Float | ValueKind::Number );
+
+        if let Some( format ) = self.kind {
+            let ok = match kind {
+                ValueKind::Int | ValueKind::Number => true,
+                ValueKind::Float => !format.is_integer(),
+                ValueKind::String | ValueKind::Bool => false,
+            };
+            if !ok {
+                return Err( FormatSpecError::NotApplicable( "kind", kind ) );
+            }
+        }
+
+        if self.sign.is_some() && !is_number {
+            return Err( FormatSpecError::NotApplicable( "sign", kind ) );
+        }
+
+        if self.zero && !is_number {
+            return Err( FormatSpecError::NotApplicable( "zero padding", kind ) );
+        }
+
+        if self.alternate && self.kind.is_none_or( | format | !format.is_integer() ) {
+            return Err( FormatSpecError::NotApplicable( "`#`", kind ) );
+        }
+
+        //  Precision is the number of decimals for floats and the max length for strings.
+        if self.precision.is_some() && !matches!( kind, ValueKind::Float | ValueKind::Number | ValueKind::String ) {
+            return Err( FormatSpecError::NotApplicable( "precision", kind ) );
+        }
+
+        Ok( () )
+    }
+
+}
+
+#[ cfg( test ) ]
+mod tests {
+    use super::*;
+
+    #[ test ]
+    fn test_parse_format_spec() {
+        assert_eq!( parse_format_spec( "" ), Ok( FormatSpec::default() ) );
+        assert_eq!( parse_format_spec( ".2" ), Ok( FormatSpec { precision: Some( 2 ), ..FormatSpec::default() } ) );
+        assert_eq!( parse_format_spec( ">10" ), Ok( FormatSpec { align: Some( Align::Right ), width: Some( 10 ), ..FormatSpec::default() } ) );
+        assert_eq!( parse_format_spec( "hex" ), Ok( FormatSpec { kind: Some( FormatKind::Hex ), ..FormatSpec::default() } ) );
+        assert_eq!( parse_format_spec( "*^10" ), Ok( FormatSpec {
+            fill: Some( '*' ),
+            align: Some( Align::Center ),
+            width: Some( 10 ),
+            ..FormatSpec::default()
+        } ) );
+        assert_eq!( parse_format_spec( "+#08.3e" ), Ok( FormatSpec {
+            sign: Some( Sign::Plus ),
+            alternate: true,
+            zero: true,
+            width: Some( 8 ),
+            precision: Some( 3 ),
+            kind: Some( FormatKind::Exp ),
+            ..FormatSpec::default()
+        } ) );
+        assert_eq!( parse_format_spec( "ф<5" ).map( | s | s.fill ), Ok( Some( 'ф' ) ) );
+        //  `<` after `<` is a fill.
+        assert_eq!( parse_format_spec( "<<" ).map( | s | ( s.fill, s.align ) ), Ok( ( Some( '<' ), Some( Align::Left ) ) ) );
+
+        assert_eq!( parse_format_spec( "q" ), Err( FormatSpecError::InvalidKind( "q".to_string() ) ) );
+        assert_eq!( parse_format_spec( "10.x" ), Err( FormatSpecError::MissingPrecision ) );
+        assert_eq!( parse_format_spec( "99999999999999999999999" ), Err( FormatSpecError::TooLarge ) );
+    }
+
+    #[ test ]
+    fn test_check() {
+        let check = | spec: &str, kind: ValueKind | parse_format_spec( spec ).unwrap().check( kind );
+
+        assert_eq!( check( ".2", ValueKind::Float ), Ok( () ) );
+        assert_eq!( check( ".2", ValueKind::String ), Ok( () ) );
+        assert_eq!( check( ".2", ValueKind::Int ), Err( FormatSpecError::NotApplicable( "precision", ValueKind::Int ) ) );
+        assert_eq!( check( "+.2", ValueKind::Number ), Ok( () ) );
+        assert_eq!( check( "#x", ValueKind::Number ), Ok( () ) );
+        assert_eq!( check( "#x", ValueKind::Int ), Ok( () ) );
+        assert_eq!( check( "x", ValueKind::Float ), Err( FormatSpecError::NotApplicable( "kind", ValueKind::Float ) ) );
+        assert_eq!( check( "e", ValueKind::Float ), Ok( () ) );
+        assert_eq!( check( "#e", ValueKind::Float ), Err( FormatSpecError::NotApplicable( "`#`", ValueKind::Float ) ) );
+        assert_eq!( check( ">10", ValueKind::Bool ), Ok( () ) );
+        assert_eq!( check( "+", ValueKind::String ), Err( FormatSpecError::NotApplicable( "sign", ValueKind::String ) ) );
+        assert_eq!( check( "05", ValueKind::Bool ), Err( FormatSpecError::NotApplicable( "zero padding", ValueKind::Bool ) ) );
+    }
+
+}
@@ -1,17 +1,7 @@
-use crate::parser::{ Parser, CompletedMarker, Skipper };
+use crate::parser::{ Parser, Marker, CompletedMarker, Skipper };
 use crate::token_set::TokenSet;
 use crate::{ SyntaxKind, TokenKind, ParserErrorKind, T };
 
-fn error_block( p: &mut Parser, error_kind: ParserErrorKind ) {
-//     assert!(p.at(T!['{']));
-//     let m = p.start();
-//     p.error(message);
-//     p.bump(T!['{']);
-//     expressions::expr_block_contents(p);
-//     p.eat(T!['}']);
-//     m.complete(p, ERROR);
-}
-
 //  ---------------------------------------------------------------------------------------------------------------  //
 
 pub fn r_source_file( p: &mut Parser ) -> CompletedMarker {
@@ -35,6 +25,12 @@ const ITEM_RECOVERY_SET: TokenSet = TokenSet::new( &[
     T![ type ],
     T![ struct ],
     T![ enum ],
+    T![ const ],
+    T![ trait ],
+    T![ impl ],
+    T![ import ],
+    T![ pub ],
+    T![ export ],
     T![ ; ],
 ] );
 
@@ -44,8 +40,6 @@ fn r_item( p: &mut Parser ) {
     }
 
     match p.current() {
-        // Some( T![ '{' ] ) => error_block( p, ParserErrorKind::ItemRequired ),
-
         // Some( T![ '}' ] if !stop_on_r_curly => {
         //     let e = p.start();
         //     p.error("unmatched `}`");
@@ -53,24 +47,99 @@ fn r_item( p: &mut Parser ) {
         //     e.complete(p, ERROR);
         // }
 
-        TokenKind::EOF | T![ '}' ] => p.error( ParserErrorKind::ItemRequired ),
+        TokenKind::EOF => p.error( ParserErrorKind::ItemRequired ),
 
         _ => p.error_and_bump( ParserErrorKind::ItemRequired ),
     }
 }
 
 fn r_opt_item( p: &mut Parser ) -> bool {
+    let m = p.start();
+
+    let visibility = r_opt_visibility( p );
+
     match p.current() {
-        T![ fn ] => r_fn( p ),
-        T![ type ] => r_type_alias( p ),
-        T![ struct ] => r_struct( p ),
-        T![ enum ] => r_enum( p ),
-        _ => return false,
+        T![ fn ] => r_fn( p, m ),
+        T![ type ] => r_type_alias( p, m ),
+        T![ struct ] => r_struct( p, m ),
+        T![ enum ] => r_enum( p, m ),
+        T![ const ] => r_const( p, m ),
+        T![ trait ] => r_trait( p, m ),
+        T![ impl ] => r_impl( p, m ),
+        T![ import ] if !visibility => r_import( p, m ),
+        _ if visibility => {
+            p.error( ParserErrorKind::ItemRequired );
+            m.complete( p, SyntaxKind::Error );
+        }
+        _ => {
+            m.abandon( p );
+            return false;
+        }
     };
 
     true
 }
 
+//  `pub` and `export` both export the item from the module.
+//
+fn r_opt_visibility( p: &mut Parser ) -> bool {
+    if !p.at( T![ pub ] ) && !p.at( T![ export ] ) {
+        return false;
+    }
+
+    let m = p.start();
+    p.eat_any();
+    m.complete( p, SyntaxKind::Visibility );
+
+    true
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+//  `import { a, b as c } from "./other"`, the path is relative to the importing file, see `modules`.
+//
+fn r_import( p: &mut Parser, m: Marker ) {
+    assert!( p.eat( T![ import ] ) );
+
+    if p.eat( T![ '{' ] ) {
+        while p.at( TokenKind::Id ) {
+            r_import_name( p );
+            if !p.eat( T![ , ] ) {
+                break;
+            }
+        }
+        p.expect( T![ '}' ] );
+
+    } else {
+        p.error( ParserErrorKind::TokenRequired( T![ '{' ] ) );
+    }
+
+    if p.at_id( "from" ) {
+        p.eat( TokenKind::Id );
+    } else {
+        p.error( ParserErrorKind::TokenRequired( TokenKind::Id ) );
+    }
+
+    if p.at( T![ '"' ] ) || p.at( TokenKind::RawString ) {
+        r_string( p );
+    } else {
+        p.error( ParserErrorKind::ImportPathRequired );
+    }
+
+    m.complete( p, SyntaxKind::Import );
+}
+
+fn r_import_name( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    r_name( p );
+    if p.eat( T![ as ] ) {
+        r_name( p );
+    }
+
+    m.complete( p, SyntaxKind::ImportName )
+}
+
 //  ---------------------------------------------------------------------------------------------------------------  //
 
 fn r_name( p: &mut Parser ) -> CompletedMarker {
@@ -100,11 +169,8 @@ fn r_generic_params( p: &mut Parser ) {
     let m = p.start();
 
     assert!( p.eat( T![ < ] ) );
-    loop {
-        match p.current() {
-            TokenKind::Id => r_generic_param( p ),
-            _ => break,
-        };
+    while p.at( TokenKind::Id ) {
+        r_generic_param( p );
         p.eat( T![ , ] );
     }
     p.expect( T![ > ] );
@@ -149,8 +215,7 @@ fn r_generic_arg( p: &mut Parser ) -> CompletedMarker {
 
 //  ---------------------------------------------------------------------------------------------------------------  //
 
-fn r_type_alias( p: &mut Parser ) {
-    let m = p.start();
+fn r_type_alias( p: &mut Parser, m: Marker ) {
 
     assert!( p.eat( T![ type ] ) );
 
@@ -163,6 +228,23 @@ fn r_type_alias( p: &mut Parser ) {
 
 //  ---------------------------------------------------------------------------------------------------------------  //
 
+//  `const PI: number = 3.14`, the value is computed at compile time, see `const_eval`.
+//
+fn r_const( p: &mut Parser, m: Marker ) {
+
+    assert!( p.eat( T![ const ] ) );
+    r_name_rec( p, ITEM_RECOVERY_SET );
+    if p.eat( T![ : ] ) {
+        r_type( p );
+    }
+    p.expect( T![ = ] );
+    r_inline_expr( p );
+
+    m.complete( p, SyntaxKind::Const );
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
 fn r_type( p: &mut Parser ) -> CompletedMarker {
     let m = p.start();
 
@@ -170,7 +252,10 @@ fn r_type( p: &mut Parser ) -> CompletedMarker {
         TokenKind::Id => r_typeref( p ),
         T![ '[' ] => r_type_array( p ),
         T![ '(' ] => r_type_tuple( p ),
-        _ => unreachable!(),
+        _ => {
+            p.error( ParserErrorKind::TypeRequired );
+            return m.complete( p, SyntaxKind::Error );
+        }
     };
 
     m.complete( p, SyntaxKind::Type )
@@ -193,7 +278,8 @@ fn r_type_array( p: &mut Parser ) -> CompletedMarker {
     assert!( p.eat( T![ '[' ] ) );
     r_type( p );
     p.expect( T![ ; ] );
-    p.eat( TokenKind::Number );
+    //  `[ number; 4 ]`, `[ number; N * 2 ]` with a constant expression.
+    r_inline_expr( p );
     p.expect( T![ ']' ] );
 
     m.complete( p, SyntaxKind::TypeArray )
@@ -202,15 +288,19 @@ fn r_type_array( p: &mut Parser ) -> CompletedMarker {
 fn r_type_tuple( p: &mut Parser ) -> CompletedMarker {
     let m = p.start();
 
-    r_tuple_field( p );
+    assert!( p.eat( T![ '(' ] ) );
+    while p.at_ts( GENERIC_TYPE_FIRST ) {
+        r_tuple_field( p );
+        p.eat( T![ , ] );
+    }
+    p.expect( T![ ')' ] );
 
     m.complete( p, SyntaxKind::TypeTuple )
 }
 
 //  ---------------------------------------------------------------------------------------------------------------  //
 
-fn r_struct( p: &mut Parser ) {
-    let m = p.start();
+fn r_struct( p: &mut Parser, m: Marker ) {
 
     assert!( p.eat( T![ struct ] ) );
     //  r_name( p );
@@ -230,7 +320,7 @@ fn r_struct_items( p: &mut Parser ) -> CompletedMarker {
     loop {
         match p.current() {
             TokenKind::Id => r_struct_field( p ),
-            TokenKind::Fn => r_fn( p ),
+            TokenKind::Fn => r_struct_method( p ),
             _ => break,
         };
         p.eat( T![ , ] );
@@ -254,10 +344,23 @@ fn r_struct_field( p: &mut Parser ) {
     m.complete( p, SyntaxKind::StructField );
 }
 
+//  `fn area( &self ) -> number { ... }`, without the receiver it's a static method: `fn new() -> Point`.
+//
+fn r_struct_method( p: &mut Parser ) {
+    let m = p.start();
+
+    assert!( p.eat( T![ fn ] ) );
+    r_name_rec( p, ITEM_RECOVERY_SET );
+    r_fn_params( p, true );
+    r_opt_fn_return_type( p );
+    r_block( p );
+
+    m.complete( p, SyntaxKind::StructMethod );
+}
+
 //  ---------------------------------------------------------------------------------------------------------------  //
 
-fn r_enum( p: &mut Parser ) {
-    let m = p.start();
+fn r_enum( p: &mut Parser, m: Marker ) {
 
     assert!( p.eat( T![ enum ] ) );
     // . r_name( p );
@@ -274,29 +377,106 @@ fn r_enum_variants( p: &mut Parser ) -> CompletedMarker {
     let m = p.start();
 
     p.expect( T![ '{' ] );
-    loop {
+    while p.at( TokenKind::Id ) {
+        let m = p.start();
+
+        r_name( p );
         match p.current() {
-            TokenKind::Id => {
-                let m = p.start();
+            T![ '(' ] => { r_tuple_fields( p ); },
+            T![ '{' ] => { r_record_fields( p ); },
+            _ => {},
+        };
 
-                r_name( p );
-                match p.current() {
-                    T![ '(' ] => { r_tuple_fields( p ); },
-                    T![ '{' ] => { r_record_fields( p ); },
-                    _ => {},
-                };
+        m.complete( p, SyntaxKind::EnumVariant );
+        p.eat( T![ , ] );
+    }
+    p.expect( T![ '}' ] );
 
-                m.complete( p, SyntaxKind::EnumVariant );
-            },
+    m.complete( p, SyntaxKind::EnumVariants )
+}
 
-            _ => break,
-        };
-        p.eat( T![ , ] );
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+//  Traits:
+//
+//      trait Show< T > {
+//          fn show( &self, x: T ) -> string;
+//          fn print( &self ) { print( self.show() ) }
+//      }
+//
+//      impl Show< number > for Point {
+//          fn show( &self, x: number ) -> string { ... }
+//      }
+//
+//  Methods with a body are default methods, impls don't have to define them.
+//  Conformance of impls is checked in `traits`.
+//
+
+fn r_trait( p: &mut Parser, m: Marker ) {
+
+    assert!( p.eat( T![ trait ] ) );
+    r_name_rec( p, ITEM_RECOVERY_SET );
+    if p.at( T![ < ] ) {
+        r_generic_params( p );
+    }
+    r_trait_items( p );
+
+    m.complete( p, SyntaxKind::Trait );
+}
 
+fn r_trait_items( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    p.expect( T![ '{' ] );
+    while p.at( T![ fn ] ) {
+        r_trait_method( p );
     }
     p.expect( T![ '}' ] );
 
-    m.complete( p, SyntaxKind::EnumVariants )
+    m.complete( p, SyntaxKind::TraitItems )
+}
+
+fn r_trait_method( p: &mut Parser ) {
+    let m = p.start();
+
+    assert!( p.eat( T![ fn ] ) );
+    r_name_rec( p, ITEM_RECOVERY_SET );
+    r_fn_params( p, true );
+    r_opt_fn_return_type( p );
+    if p.at( T![ '{' ] ) {
+        r_block( p );
+    } else {
+        p.expect( T![ ; ] );
+    }
+
+    m.complete( p, SyntaxKind::TraitMethod );
+}
+
+fn r_impl( p: &mut Parser, m: Marker ) {
+
+    assert!( p.eat( T![ impl ] ) );
+    if p.at( TokenKind::Id ) {
+        r_typeref( p );
+    } else {
+        p.error( ParserErrorKind::TypeRequired );
+    }
+    p.expect( T![ for ] );
+    r_type( p );
+    r_impl_items( p );
+
+    m.complete( p, SyntaxKind::Impl );
+}
+
+fn r_impl_items( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    p.expect( T![ '{' ] );
+    while p.at( T![ fn ] ) {
+        r_struct_method( p );
+    }
+    p.expect( T![ '}' ] );
+
+    m.complete( p, SyntaxKind::ImplItems )
 }
 
 //  ---------------------------------------------------------------------------------------------------------------  //
@@ -305,11 +485,8 @@ fn r_tuple_fields( p: &mut Parser ) -> CompletedMarker {
     let m = p.start();
 
     p.expect( T![ '(' ] );
-    loop {
-        match p.current() {
-            TokenKind::Id => r_tuple_field( p ),
-            _ => break,
-        };
+    while p.at( TokenKind::Id ) {
+        r_tuple_field( p );
         p.eat( T![ , ] );
     }
     p.expect( T![ ')' ] );
@@ -330,11 +507,8 @@ fn r_record_fields( p: &mut Parser ) -> CompletedMarker {
     let m = p.start();
 
     p.expect( T![ '{' ] );
-    loop {
-        match p.current() {
-            TokenKind::Id => r_record_field( p ),
-            _ => break,
-        };
+    while p.at( TokenKind::Id ) {
+        r_record_field( p );
         p.eat( T![ , ] );
     }
     p.expect( T![ '}' ] );
@@ -354,24 +528,33 @@ fn r_record_field( p: &mut Parser ) -> CompletedMarker {
 
 //  ---------------------------------------------------------------------------------------------------------------  //
 
-fn r_fn( p: &mut Parser ) {
-    let m = p.start();
+fn r_fn( p: &mut Parser, m: Marker ) {
 
     assert!( p.eat( T![ fn ] ) );
     r_name_rec( p, ITEM_RECOVERY_SET );
-    r_fn_params( p );
+    r_fn_params( p, false );
     r_opt_fn_return_type( p );
     r_block( p );
 
     m.complete( p, SyntaxKind::Fn );
 }
 
-fn r_fn_params( p: &mut Parser ) {
+//  `receiver` allows `self` or `&self` as the first parameter of methods.
+//
+fn r_fn_params( p: &mut Parser, receiver: bool ) {
     if p.at( T![ '(' ] ) {
         let m = p.start();
 
         p.eat( T![ '(' ] );
+        if receiver && ( p.at( T![ & ] ) || p.at_id( "self" ) ) {
+            r_self_param( p );
+            p.eat( T![ , ] );
+        }
         while !p.at_eof() && !p.at( T![ ')' ] ) {
+            if !p.at( TokenKind::Id ) {
+                p.error( ParserErrorKind::NameRequired );
+                break;
+            }
             r_fn_param( p );
 
             p.eat( T![ , ] );
@@ -389,6 +572,19 @@ fn r_fn_params( p: &mut Parser ) {
 
 }
 
+fn r_self_param( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    p.eat( T![ & ] );
+    if p.at_id( "self" ) {
+        p.eat( TokenKind::Id );
+    } else {
+        p.error( ParserErrorKind::SelfRequired );
+    }
+
+    m.complete( p, SyntaxKind::SelfParam )
+}
+
 fn r_fn_param( p: &mut Parser ) -> CompletedMarker {
     let m = p.start();
 
@@ -431,26 +627,364 @@ fn r_if( p: &mut Parser ) -> CompletedMarker {
     let m = p.start();
 
     assert!( p.eat( T![ if ] ) );
-    r_inline_expr( p );
+    r_cond_expr( p );
     r_block( p );
 
+    //  `r_block` skips trivia after `}` (or the dedent) with the `Block` skipper,
+    //  so `else` on the next line is still the current token here.
+    if p.at( T![ else ] ) {
+        r_else( p );
+    }
+
     m.complete( p, SyntaxKind::If )
 }
 
-fn r_while( p: &mut Parser ) -> CompletedMarker {
+fn r_else( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    assert!( p.eat( T![ else ] ) );
+    if p.at( T![ if ] ) {
+        r_if( p );
+
+    } else {
+        r_block( p );
+    }
+
+    m.complete( p, SyntaxKind::Else )
+}
+
+//  Optionally labeled loop:
+//
+//      while x < n { }
+//      for x in 0 .. n { }
+//      for ( i, x ) in xs { }
+//      'outer: for x in xs { }
+//
+fn r_loop( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    if p.at( TokenKind::Label ) {
+        r_loop_label( p );
+    }
+
+    match p.current() {
+        T![ while ] => {
+            p.eat( T![ while ] );
+            r_cond_expr( p );
+            r_block( p );
+
+            m.complete( p, SyntaxKind::While )
+        }
+
+        T![ for ] => {
+            p.eat( T![ for ] );
+            r_pattern( p );
+            p.expect( T![ in ] );
+            r_cond_expr( p );
+            r_block( p );
+
+            m.complete( p, SyntaxKind::For )
+        }
+
+        _ => {
+            p.error( ParserErrorKind::LoopRequired );
+
+            m.complete( p, SyntaxKind::Error )
+        }
+    }
+}
+
+fn r_loop_label( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    assert!( p.eat( TokenKind::Label ) );
+    p.expect( T![ : ] );
+
+    m.complete( p, SyntaxKind::LoopLabel )
+}
+
+//  `return [expr]`, `break [label] [expr]` and `continue [label]`.
+//  Label and value have to be on the same line as the keyword.
+//
+fn r_jump( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    p.set_skipper( Skipper::Inline );
+
+    let kind = match p.current() {
+        T![ return ] => SyntaxKind::Return,
+        T![ break ] => SyntaxKind::Break,
+        _ => SyntaxKind::Continue,
+    };
+    p.eat_any();
+
+    if kind != SyntaxKind::Return {
+        p.eat( TokenKind::Label );
+    }
+    if kind != SyntaxKind::Continue && p.at_ts( EXPR_FIRST ) {
+        r_inline_expr( p );
+    }
+
+    p.restore_skipper();
+
+    m.complete( p, kind )
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+//  Patterns of `for` loops and `match` arms:
+//
+//      _  x  1  -1  "s"  true  null
+//      ( a, ( b, _ ) )
+//      Some( x )
+//      Point { x, y: 0, .. }
+//      A | B( _ )
+//
+//  A bare name is parsed as a binding, unit variants are told apart by name resolution.
+//
+const PATTERN_FIRST: TokenSet = TokenSet::new( &[
+    T![ _ ],
+    TokenKind::Id,
+    T![ '(' ],
+    T![ - ],
+    TokenKind::Number,
+    T![ true ],
+    T![ false ],
+    T![ null ],
+    T![ '"' ],
+    T![ "\"\"\"" ],
+    TokenKind::RawString,
+] );
+
+fn r_pattern( p: &mut Parser ) -> CompletedMarker {
+    let first = r_single_pattern( p );
+
+    if !p.at( T![ | ] ) {
+        return first;
+    }
+
+    let m = first.precede( p );
+    while p.eat( T![ | ] ) {
+        r_single_pattern( p );
+    }
+
+    m.complete( p, SyntaxKind::OrPattern )
+}
+
+fn r_single_pattern( p: &mut Parser ) -> CompletedMarker {
+    match p.current() {
+        T![ _ ] => {
+            let m = p.start();
+            p.eat( T![ _ ] );
+            m.complete( p, SyntaxKind::WildcardPattern )
+        }
+
+        T![ '(' ] => {
+            let m = p.start();
+            r_pattern_list( p );
+            m.complete( p, SyntaxKind::TuplePattern )
+        }
+
+        TokenKind::Id => {
+            let m = p.start();
+
+            r_name( p );
+            match p.current() {
+                T![ '(' ] => {
+                    r_pattern_list( p );
+                    m.complete( p, SyntaxKind::VariantPattern )
+                }
+
+                T![ '{' ] => {
+                    r_record_pattern_fields( p );
+                    m.complete( p, SyntaxKind::RecordPattern )
+                }
+
+                _ => m.complete( p, SyntaxKind::BindingPattern ),
+            }
+        }
+
+        _ if p.at_ts( PATTERN_FIRST ) => r_literal_pattern( p ),
+
+        _ => {
+            let m = p.start();
+            p.error( ParserErrorKind::PatternRequired );
+            m.complete( p, SyntaxKind::Error )
+        }
+    }
+}
+
+//  `( a, b )` of tuple and variant patterns.
+//
+fn r_pattern_list( p: &mut Parser ) {
+    assert!( p.eat( T![ '(' ] ) );
+    while p.at_ts( PATTERN_FIRST ) {
+        r_pattern( p );
+        p.eat( T![ , ] );
+    }
+    p.expect( T![ ')' ] );
+}
+
+//  `{ x, y: pattern, .. }` of record variant patterns.
+//
+fn r_record_pattern_fields( p: &mut Parser ) {
+    assert!( p.eat( T![ '{' ] ) );
+    while !p.at_eof() && !p.at( T![ '}' ] ) {
+        if p.eat( T![ .. ] ) {
+            break;
+        }
+        if !p.at( TokenKind::Id ) {
+            p.error( ParserErrorKind::NameRequired );
+            break;
+        }
+        r_record_pattern_field( p );
+        p.eat( T![ , ] );
+    }
+    p.expect( T![ '}' ] );
+}
+
+fn r_record_pattern_field( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    r_name( p );
+    if p.eat( T![ : ] ) {
+        r_pattern( p );
+    }
+
+    m.complete( p, SyntaxKind::RecordPatternField )
+}
+
+fn r_literal_pattern( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    match p.current() {
+        T![ - ] => {
+            let minus = p.start();
+            p.eat( T![ - ] );
+            r_inline_number( p );
+            minus.complete( p, SyntaxKind::InlineUnary );
+        }
+        TokenKind::Number => { r_inline_number( p ); },
+        T![ true ] | T![ false ] => { r_inline_literal( p, SyntaxKind::InlineBool ); },
+        T![ null ] => { r_inline_literal( p, SyntaxKind::InlineNull ); },
+        _ => { r_string( p ); },
+    }
+
+    m.complete( p, SyntaxKind::LiteralPattern )
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+//  `match x { pattern => expr, ... }` or with the offside rule:
+//
+//      match x:
+//          Some( y ) if y > 0 => y
+//          _ => 0
+//
+fn r_match( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    assert!( p.eat( T![ match ] ) );
+    r_cond_expr( p );
+
+    p.set_skipper( Skipper::Block );
+    p.set_struct_literals( true );
+
+    let end = if p.eat( T![ : ] ) {
+        p.expect( TokenKind::Indent );
+        TokenKind::Dedent
+
+    } else {
+        p.expect( T![ '{' ] );
+        T![ '}' ]
+    };
+
+    while !p.at_eof() && !p.at( end ) {
+        if p.at_ts( PATTERN_FIRST ) {
+            r_match_arm( p );
+
+        } else {
+            let m = p.start();
+            p.error_and_bump( ParserErrorKind::PatternRequired );
+            m.complete( p, SyntaxKind::Error );
+        }
+        p.eat( T![ , ] );
+    }
+
+    //  `match` is an expression, trivia after it belongs to the enclosing statement.
+    p.restore_struct_literals();
+    p.restore_skipper();
+    p.eat( end );
+
+    m.complete( p, SyntaxKind::Match )
+}
+
+fn r_match_arm( p: &mut Parser ) -> CompletedMarker {
     let m = p.start();
 
-    assert!( p.eat( T![ while ] ) );
+    r_pattern( p );
+    if p.at( T![ if ] ) {
+        r_match_guard( p );
+    }
+    p.expect( T![ => ] );
+
+    match p.current() {
+        T![ '{' ] => { r_block( p ); },
+        T![ return ] | T![ break ] | T![ continue ] => { r_jump( p ); },
+        _ => { r_inline_expr( p ); },
+    }
+
+    m.complete( p, SyntaxKind::MatchArm )
+}
+
+fn r_match_guard( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    assert!( p.eat( T![ if ] ) );
     r_inline_expr( p );
-    r_block( p );
 
-    m.complete( p, SyntaxKind::While )
+    m.complete( p, SyntaxKind::MatchGuard )
 }
 
 //  ---------------------------------------------------------------------------------------------------------------  //
 
+const EXPR_FIRST: TokenSet = TokenSet::new( &[
+    T![ '(' ],
+    T![ '[' ],
+    T![ + ],
+    T![ - ],
+    T![ ! ],
+    TokenKind::Number,
+    TokenKind::Id,
+    T![ true ],
+    T![ false ],
+    T![ null ],
+    T![ match ],
+    T![ fn ],
+    T![ | ],
+    T![ || ],
+    T![ '"' ],
+    T![ "\"\"\"" ],
+    TokenKind::RawString,
+] );
+
+//  Expression followed by a block: condition of `if` and `while`, iterable of `for`, scrutinee
+//  of `match`. `{` after a name starts the block, not a struct literal.
+//
+fn r_cond_expr( p: &mut Parser ) -> CompletedMarker {
+    p.set_struct_literals( false );
+    let m = r_inline_expr( p );
+    p.restore_struct_literals();
+
+    m
+}
+
 fn r_inline_expr( p: &mut Parser ) -> CompletedMarker {
-    p.set_skipper( Skipper::Inline );
+    let skipper = match p.skipper() {
+        Skipper::Multiline => Skipper::Multiline,
+        _ => Skipper::Inline,
+    };
+    p.set_skipper( skipper );
 
     let m = r_inline_binary( p, 0 );
 
@@ -459,14 +993,32 @@ fn r_inline_expr( p: &mut Parser ) -> CompletedMarker {
     m
 }
 
+pub const ASSIGN_OPS: TokenSet = TokenSet::new( &[
+    T![ = ],
+    T![ += ],
+    T![ -= ],
+    T![ *= ],
+    T![ /= ],
+    T![ %= ],
+    T![ &&= ],
+    T![ ||= ],
+] );
+
+//  `|` is not a binary operator, and `||` in place of an operand is a closure without parameters
+//  (see `r_inline_primary`), so closures don't clash with `a || b`.
+//
 fn infix_binding_power( kind: TokenKind ) -> Option< ( i8, i8 ) > {
     match kind {
-        T![ || ] => Some( ( 1, 2 ) ),
-        T![ && ] => Some( ( 3, 4 ) ),
-        T![ == ] | T![ != ] => Some( ( 5, 6 ) ),
-        T![ < ] | T![ <= ] | T![ > ] | T![ >= ] => Some( ( 7, 8 ) ),
-        T![ + ] | T![ - ] => Some( ( 9, 10 ) ),
-        T![ * ] | T![ / ] | T![ % ] => Some( ( 11, 12 ) ),
+        //  Assignments are right associative: `a = b = c` is `a = ( b = c )`.
+        _ if ASSIGN_OPS.contains( kind ) => Some( ( 2, 1 ) ),
+        //  `a .. b` and `a ... b` (inclusive) bind weaker than any other operator.
+        T![ .. ] | T![ ... ] => Some( ( 3, 4 ) ),
+        T![ || ] => Some( ( 5, 6 ) ),
+        T![ && ] => Some( ( 7, 8 ) ),
+        T![ == ] | T![ != ] => Some( ( 9, 10 ) ),
+        T![ < ] | T![ <= ] | T![ > ] | T![ >= ] => Some( ( 11, 12 ) ),
+        T![ + ] | T![ - ] => Some( ( 13, 14 ) ),
+        T![ * ] | T![ / ] | T![ % ] => Some( ( 15, 16 ) ),
         _ => None,
     }
 }
@@ -481,9 +1033,14 @@ fn r_inline_binary( p: &mut Parser, min_bp: i8 ) -> CompletedMarker {
         } else {
             let m = left.precede( p );
 
+            let kind = match p.current() {
+                T![ .. ] | T![ ... ] => SyntaxKind::InlineRange,
+                op if ASSIGN_OPS.contains( op ) => SyntaxKind::Assign,
+                _ => SyntaxKind::InlineBinary,
+            };
             p.eat_any();
             r_inline_binary( p, right_bp );
-            left = m.complete( p, SyntaxKind::InlineBinary );
+            left = m.complete( p, kind );
         }
 
     }
@@ -506,16 +1063,32 @@ fn r_inline_unary( p: &mut Parser ) -> CompletedMarker {
 fn r_inline_primary( p: &mut Parser ) -> CompletedMarker {
     let mut expr = match p.current() {
         T![ '(' ] => r_inline_subexpr( p ),
+        T![ '[' ] => r_inline_array( p ),
         TokenKind::Number => r_inline_number( p ),
+        T![ true ] | T![ false ] => r_inline_literal( p, SyntaxKind::InlineBool ),
+        T![ null ] => r_inline_literal( p, SyntaxKind::InlineNull ),
         TokenKind::Id => r_inline_var( p ),
-        T![ '"' ] => r_string( p ),
-        _ => unreachable!(),
+        T![ match ] => r_match( p ),
+        T![ fn ] | T![ | ] | T![ || ] => r_closure( p ),
+        T![ '"' ] | T![ "\"\"\"" ] | TokenKind::RawString => r_string( p ),
+        _ => {
+            let m = p.start();
+            p.error( ParserErrorKind::ExpressionRequired );
+            return m.complete( p, SyntaxKind::Error );
+        }
     };
 
     loop {
         if p.at( T![ . ] ) {
             let m = expr.precede( p );
             p.eat( T![ . ] );
+
+            //  `t.0`
+            if p.eat( TokenKind::Number ) {
+                expr = m.complete( p, SyntaxKind::InlineTupleField );
+                continue;
+            }
+
             p.eat( TokenKind::Id );
 
             if p.at( T![ '(' ] ) {
@@ -532,7 +1105,25 @@ fn r_inline_primary( p: &mut Parser ) -> CompletedMarker {
 
             r_inline_args( p );
 
-            m.complete( p, SyntaxKind::InlineCall );
+            expr = m.complete( p, SyntaxKind::InlineCall );
+
+        } else if p.at( T![ '[' ] ) {
+            let m = expr.precede( p );
+
+            p.set_struct_literals( true );
+            p.eat( T![ '[' ] );
+            r_inline_expr( p );
+            p.expect( T![ ']' ] );
+            p.restore_struct_literals();
+
+            expr = m.complete( p, SyntaxKind::InlineIndex );
+
+        } else if p.at( T![ '{' ] ) && p.struct_literals() && matches!( expr.kind(), SyntaxKind::InlineVar | SyntaxKind::InlineField ) {
+            let m = expr.precede( p );
+
+            r_inline_struct_fields( p );
+
+            expr = m.complete( p, SyntaxKind::InlineStruct );
 
         } else {
             break expr;
@@ -540,14 +1131,104 @@ fn r_inline_primary( p: &mut Parser ) -> CompletedMarker {
     }
 }
 
+//  `( a )` is a parenthesized expression, `()`, `( a, )` and `( a, b )` are tuples.
+//  Newlines inside of brackets are insignificant.
+//
 fn r_inline_subexpr( p: &mut Parser ) -> CompletedMarker {
     let m = p.start();
 
+    p.set_skipper( Skipper::Multiline );
+    p.set_struct_literals( true );
+
     assert!( p.eat( T![ '(' ] ) );
-    r_inline_expr( p );
+    let mut kind = SyntaxKind::InlineTuple;
+    if p.at_ts( EXPR_FIRST ) {
+        r_inline_expr( p );
+        if p.at( T![ , ] ) {
+            r_inline_list_rest( p );
+
+        } else {
+            kind = SyntaxKind::InlineSubexpr;
+        }
+    }
+
+    p.restore_struct_literals();
+    p.restore_skipper();
     p.expect( T![ ')' ] );
 
-    m.complete( p, SyntaxKind::InlineSubexpr )
+    m.complete( p, kind )
+}
+
+//  `[ a, b, c ]` or `[ x; n ]`.
+//
+fn r_inline_array( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    p.set_skipper( Skipper::Multiline );
+    p.set_struct_literals( true );
+
+    assert!( p.eat( T![ '[' ] ) );
+    let mut kind = SyntaxKind::InlineArray;
+    if p.at_ts( EXPR_FIRST ) {
+        r_inline_expr( p );
+        if p.eat( T![ ; ] ) {
+            r_inline_expr( p );
+            kind = SyntaxKind::InlineArrayRepeat;
+
+        } else {
+            r_inline_list_rest( p );
+        }
+    }
+
+    p.restore_struct_literals();
+    p.restore_skipper();
+    p.expect( T![ ']' ] );
+
+    m.complete( p, kind )
+}
+
+//  `Foo { x: 1, y, ..base }` after `Foo`, also `Shape.Circle { r: 1 }`.
+//
+//  Tuple variants are constructed with a call, `Some( x )`, and are told apart from calls by name resolution.
+//
+fn r_inline_struct_fields( p: &mut Parser ) {
+    p.set_skipper( Skipper::Multiline );
+    p.set_struct_literals( true );
+
+    assert!( p.eat( T![ '{' ] ) );
+    while !p.at_eof() && !p.at( T![ '}' ] ) {
+        if p.at( T![ .. ] ) {
+            let m = p.start();
+            p.eat( T![ .. ] );
+            r_inline_expr( p );
+            m.complete( p, SyntaxKind::InlineStructSpread );
+
+        } else if p.at( TokenKind::Id ) {
+            let m = p.start();
+            r_name( p );
+            if p.eat( T![ : ] ) {
+                r_inline_expr( p );
+            }
+            m.complete( p, SyntaxKind::InlineStructField );
+
+        } else {
+            p.error( ParserErrorKind::NameRequired );
+            break;
+        }
+        p.eat( T![ , ] );
+    }
+
+    p.restore_struct_literals();
+    p.restore_skipper();
+    p.expect( T![ '}' ] );
+}
+
+//  `, b, c` after the first element, with an optional trailing comma.
+//
+fn r_inline_list_rest( p: &mut Parser ) {
+    while p.eat( T![ , ] ) && p.at_ts( EXPR_FIRST ) {
+        r_inline_expr( p );
+    }
 }
 
 fn r_inline_number( p: &mut Parser ) -> CompletedMarker {
@@ -558,6 +1239,14 @@ fn r_inline_number( p: &mut Parser ) -> CompletedMarker {
     m.complete( p, SyntaxKind::InlineNumber )
 }
 
+fn r_inline_literal( p: &mut Parser, kind: SyntaxKind ) -> CompletedMarker {
+    let m = p.start();
+
+    p.eat_any();
+
+    m.complete( p, kind )
+}
+
 fn r_inline_var( p: &mut Parser ) -> CompletedMarker {
     let m = p.start();
 
@@ -569,13 +1258,21 @@ fn r_inline_var( p: &mut Parser ) -> CompletedMarker {
 fn r_inline_args( p: &mut Parser ) -> CompletedMarker {
     let m = p.start();
 
+    p.set_struct_literals( true );
+
     assert!( p.eat( T![ '(' ] ) );
-    while !p.at( T![ ')' ] ) && !p.at_eol() {
+    while !p.at_eof() && !p.at( T![ ')' ] ) && !p.at_eol() {
+        if !p.at_ts( EXPR_FIRST ) {
+            p.error( ParserErrorKind::ExpressionRequired );
+            break;
+        }
         r_inline_arg( p );
         p.eat( T![ , ] );
     }
     p.expect( T![ ')' ] );
 
+    p.restore_struct_literals();
+
     m.complete( p, SyntaxKind::CallArgs )
 }
 
@@ -587,21 +1284,95 @@ fn r_inline_arg( p: &mut Parser ) -> CompletedMarker {
     m.complete( p, SyntaxKind::CallArg )
 }
 
+//  Closures:
+//
+//      | x: number, y | x * y
+//      || 42
+//      | x | -> number { x * 2 }
+//      fn ( x: number ) -> number { x * 2 }
+//      fn ( x ):
+//          x * 2
+//
+//  Body of `| |` closures without a return type is an inline expression unless it starts with `{`.
+//
+fn r_closure( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    if p.eat( T![ fn ] ) {
+        r_closure_params( p, T![ '(' ], T![ ')' ] );
+        r_opt_fn_return_type( p );
+        r_inline_block( p );
+
+    } else {
+        r_closure_params( p, T![ | ], T![ | ] );
+        if p.at( T![ -> ] ) {
+            r_opt_fn_return_type( p );
+            r_inline_block( p );
+
+        } else if p.at( T![ '{' ] ) {
+            r_inline_block( p );
+
+        } else {
+            r_inline_expr( p );
+        }
+    }
+
+    m.complete( p, SyntaxKind::Closure )
+}
+
+fn r_closure_params( p: &mut Parser, open: TokenKind, close: TokenKind ) -> CompletedMarker {
+    let m = p.start();
+
+    if !( open == T![ | ] && p.eat( T![ || ] ) ) {
+        p.expect( open );
+        while p.at( TokenKind::Id ) {
+            r_closure_param( p );
+            p.eat( T![ , ] );
+        }
+        p.expect( close );
+    }
+
+    m.complete( p, SyntaxKind::ClosureParams )
+}
+
+//  Unlike `r_fn_param` the type is optional.
+//
+fn r_closure_param( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    p.eat( TokenKind::Id );
+    if p.eat( T![ : ] ) {
+        r_type( p );
+    }
+
+    m.complete( p, SyntaxKind::ClosureParam )
+}
+
 fn r_string( p: &mut Parser ) -> CompletedMarker {
     let m = p.start();
 
     p.set_skipper( Skipper::None );
 
-    assert!( p.eat( T![ '"' ] ) );
+    if p.eat( TokenKind::RawString ) {
+        p.restore_skipper();
+
+        return m.complete( p, SyntaxKind::String );
+    }
+
+    //  `"""` strings can span lines.
+    let quote = p.current();
+    let multiline = quote == T![ "\"\"\"" ];
+
+    assert!( p.eat( quote ) );
 
-    while !p.at_eol() && !p.at( T![ '"' ] ){
+    while !p.at_eof() && !p.at( quote ) && ( multiline || !p.at_eol() ) {
         match p.current() {
             TokenKind::StringFragment => {
                 r_string_fragment( p );
             }
 
             TokenKind::DollarOpenBrace => {
-                r_string_expr( p );
+                r_string_expr( p, multiline );
             }
 
             _ => {
@@ -610,7 +1381,7 @@ fn r_string( p: &mut Parser ) -> CompletedMarker {
         }
     }
 
-    p.expect( T![ '"' ] );
+    p.expect( quote );
 
     p.restore_skipper();
 
@@ -625,15 +1396,19 @@ fn r_string_fragment( p: &mut Parser ) -> CompletedMarker {
     m.complete( p, SyntaxKind::StringFragment )
 }
 
-fn r_string_expr( p: &mut Parser ) -> CompletedMarker {
+fn r_string_expr( p: &mut Parser, multiline: bool ) -> CompletedMarker {
     let m = p.start();
 
-    p.set_skipper( Skipper::Inline );
+    p.set_skipper( if multiline { Skipper::Multiline } else { Skipper::Inline } );
+    p.set_struct_literals( true );
 
     assert!( p.eat( TokenKind::DollarOpenBrace ) );
     r_inline_expr( p );
+    //  `${ price:.2 }`
+    p.eat( TokenKind::FormatSpec );
     p.eat( T![ '}' ] );
 
+    p.restore_struct_literals();
     p.restore_skipper();
 
     m.complete( p, SyntaxKind::StringExpr )
@@ -645,18 +1420,59 @@ fn r_block( p: &mut Parser ) -> CompletedMarker {
     let m = p.start();
 
     p.set_skipper( Skipper::Block );
+    let end = r_block_statements( p );
+    p.eat( end );
+    p.restore_skipper();
 
-    p.expect( T![ '{' ] );
-    while !p.at_eof() && !p.at( T![ '}' ] ) {
-        r_block_statement( p );
-    }
-    p.eat( T![ '}' ] );
+    m.complete( p, SyntaxKind::Block )
+}
 
+//  Block inside of an expression, e.g. a closure body. Unlike `r_block` trivia after it
+//  belongs to the enclosing statement.
+//
+fn r_inline_block( p: &mut Parser ) -> CompletedMarker {
+    let m = p.start();
+
+    p.set_skipper( Skipper::Block );
+    let end = r_block_statements( p );
     p.restore_skipper();
+    p.eat( end );
 
     m.complete( p, SyntaxKind::Block )
 }
 
+//  Statements up to the closing `}` or dedent, which is returned and not eaten.
+//
+fn r_block_statements( p: &mut Parser ) -> TokenKind {
+    p.set_struct_literals( true );
+
+    let end = if p.eat( T![ : ] ) {
+        //  Offside rule block:
+        //
+        //      fn add( x: number, y: number ) -> number:
+        //          x + y
+        //
+        p.expect( TokenKind::Indent );
+        while !p.at_eof() && !p.at( TokenKind::Dedent ) {
+            r_block_statement( p );
+        }
+
+        TokenKind::Dedent
+
+    } else {
+        p.expect( T![ '{' ] );
+        while !p.at_eof() && !p.at( T![ '}' ] ) {
+            r_block_statement( p );
+        }
+
+        T![ '}' ]
+    };
+
+    p.restore_struct_literals();
+
+    end
+}
+
 fn r_expr( p: &mut Parser ) -> CompletedMarker {
     let m = p.start();
 
@@ -668,8 +1484,14 @@ fn r_expr( p: &mut Parser ) -> CompletedMarker {
 fn r_block_statement( p: &mut Parser ) -> CompletedMarker {
     match p.current() {
         T![ if ] => r_if( p ),
-        T![ while ] => r_while( p ),
+        T![ while ] | T![ for ] | TokenKind::Label => r_loop( p ),
+        T![ return ] | T![ break ] | T![ continue ] => r_jump( p ),
         T![ let ] => r_let( p ),
-        _ => r_expr( p ),
+        _ if p.at_ts( EXPR_FIRST ) => r_expr( p ),
+        _ => {
+            let m = p.start();
+            p.error_and_bump( ParserErrorKind::ExpressionRequired );
+            m.complete( p, SyntaxKind::Error )
+        }
     }
 }
@@ -1,6 +1,14 @@
 use std::fmt;
+use std::ops::Range;
 
 use crate::token_kind::{ TokenKind, T };
+use crate::lexer_error::{ LexerError, LexerErrorKind };
+use crate::unescape::unescape;
+use crate::format_spec::parse_format_spec;
+use crate::syntax_node::{ column, DEFAULT_TAB_WIDTH };
+
+use unicode_ident::{ is_xid_start, is_xid_continue };
+use memchr::{ memchr, memchr2, memchr3 };
 
 #[ derive( Clone, Copy ) ]
 pub struct Token< 'a > {
@@ -26,6 +34,13 @@ enum State {
     StringExpr,
 }
 
+//  Single line `"..."` or multi-line `"""..."""` string.
+#[ derive( Copy, Clone, PartialEq, Debug ) ]
+enum Quote {
+    Single,
+    Triple,
+}
+
 #[ derive( Clone ) ]
 pub struct Lexer < 'a> {
     input: &'a str,
@@ -35,7 +50,25 @@ pub struct Lexer < 'a> {
 
     state: State,
     n_opened_curlies: u32,
-    opened_quotes: Vec< u32 >,
+    quote: Quote,
+    opened_quotes: Vec< ( u32, Quote ) >,
+    //  Positions of opening quotes of the currently opened strings, for errors.
+    string_starts: Vec< usize >,
+
+    //  Offside rule: widths of the currently opened indentation levels
+    //  with the number of brackets opened before each level.
+    track_indents: bool,
+    indents: Vec< ( usize, u32 ) >,
+    indent_width: Option< usize >,
+    pending_dedents: usize,
+    at_line_start: bool,
+    //  The last token (not counting trivia) is `:`, so a more indented line opens a block.
+    after_colon: bool,
+    line_start: usize,
+    n_opened_brackets: u32,
+    tab_width: usize,
+
+    errors: Vec< LexerError >,
 }
 
 impl < 'a > Lexer < 'a >{
@@ -48,30 +81,115 @@ impl < 'a > Lexer < 'a >{
 
             state: State::Normal,
             n_opened_curlies: 0,
+            quote: Quote::Single,
             opened_quotes: Vec::new(),
+            string_starts: Vec::new(),
+
+            track_indents: false,
+            indents: vec![ ( 0, 0 ) ],
+            indent_width: None,
+            pending_dedents: 0,
+            at_line_start: true,
+            after_colon: false,
+            line_start: 0,
+            n_opened_brackets: 0,
+            tab_width: DEFAULT_TAB_WIDTH,
+
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn with_indents( input: &'a str ) -> Self {
+        Lexer {
+            track_indents: true,
+            ..Lexer::new( input )
+        }
+    }
+
+    //  Tabs in indentation advance to the next multiple of `tab_width`.
+    pub fn with_tab_width( self, tab_width: usize ) -> Self {
+        Lexer {
+            tab_width,
+            ..self
         }
     }
 
+    pub fn input( &self ) -> &'a str {
+        self.input
+    }
+
+    pub fn errors( &self ) -> &[ LexerError ] {
+        &self.errors
+    }
+
+    fn error( &mut self, start: usize, end: usize, kind: LexerErrorKind ) {
+        self.errors.push( LexerError::new( start, end, kind ) );
+    }
+
     fn at( &self, pos: usize ) -> Option< u8 > {
         self.bytes
             .get( pos )
-            .map( | x | *x )
+            .copied()
     }
 
     fn byte_is( &self, pos: usize, b: u8 ) -> bool {
-        match self.bytes.get( pos) {
-            Some( &x ) if x == b => true,
-            _ => false,
-        }
+        self.at( pos ) == Some( b )
     }
 
     fn byte_matches( &self, pos: usize, f: impl Fn( u8 ) -> bool ) -> bool {
-        match self.bytes.get( pos ) {
-            Some( &b ) if f( b ) => true,
-            _ => false,
+        matches!( self.at( pos ), Some( b ) if f( b ) )
+    }
+
+    fn char_at( &self, pos: usize ) -> char {
+        self.input[ pos .. ].chars().next().unwrap()
+    }
+
+    //  Identifiers are XID_Start XID_Continue*, plus `_` as a start char.
+    //
+    fn id_end( &self, pos: usize ) -> usize {
+        let mut i = pos;
+
+        loop {
+            i = self.iterate_while( i, is_id_next );
+
+            match self.at( i ) {
+                Some( 0x80 .. ) => {
+                    let c = self.char_at( i );
+                    if !is_xid_continue( c ) {
+                        break i;
+                    }
+                    i += c.len_utf8();
+                }
+                _ => break i,
+            }
         }
     }
 
+    //  Runs of unknown chars end on a char boundary.
+    //
+    fn error_end( &self, pos: usize ) -> usize {
+        self.input[ pos .. ]
+            .char_indices()
+            .find( | &( _, c ) | !is_error( c ) )
+            .map_or( self.input.len(), | ( i, _ ) | pos + i )
+    }
+
+    //  `\n` or `\r\n`.
+    //
+    fn eol_end( &self, pos: usize ) -> Option< usize > {
+        match self.at( pos ) {
+            Some( b'\n' ) => Some( pos + 1 ),
+            Some( b'\r' ) if self.byte_is( pos + 1, b'\n' ) => Some( pos + 2 ),
+            _ => None,
+        }
+    }
+
+    //  Width of the indentation from the line start to `pos`.
+    //
+    fn indent_width_at( &self, pos: usize ) -> usize {
+        column( &self.input[ self.line_start .. pos ], self.tab_width )
+    }
+
     fn iterate_while( &self, pos: usize, f: impl Fn( u8 ) -> bool ) -> usize {
         let mut i = pos;
 
@@ -83,12 +201,394 @@ impl < 'a > Lexer < 'a >{
         }
     }
 
+    //  Indent and Dedent are zero-width tokens placed right before the first token of a line,
+    //  i.e. after the leading spaces. Blank and comment-only lines do not change the indentation.
+    //
+    //  Only a line after a line ending with `:` opens a block, other more indented lines
+    //  are continuation lines:
+    //
+    //      type X =
+    //          number
+    //
+    //  Blocks can be opened inside brackets. Lines inside brackets opened after the current block
+    //  are not tracked, and a closing bracket closes the blocks opened inside it:
+    //
+    //      fn f() {
+    //          if x:
+    //              y }
+    //
+    fn get_indent_kind( &mut self ) -> Option< TokenKind > {
+        if self.pending_dedents > 0 {
+            self.pending_dedents -= 1;
+
+            return Some( TokenKind::Dedent );
+        }
+
+        let pos = self.pos;
+
+        match self.at( pos ) {
+            None => {
+                if self.indents.len() > 1 {
+                    self.indents.pop();
+                    self.at_line_start = false;
+
+                    Some( TokenKind::Dedent )
+
+                } else {
+                    None
+                }
+            }
+
+            Some( b ) if is_space( b ) || b == b'\r' || b == b'\n' => None,
+
+            Some( b'/' ) if self.byte_is( pos + 1, b'/' ) => None,
+
+            Some( b'/' ) if self.byte_is( pos + 1, b'*' ) && self.is_comment_only( pos ) => None,
+
+            Some( b ) => {
+                if self.at_line_start {
+                    self.at_line_start = false;
+
+                    if let Some( kind ) = self.get_line_indent_kind( pos ) {
+                        return Some( kind );
+                    }
+                }
+
+                let ( _, n_brackets ) = *self.indents.last().unwrap();
+                if matches!( b, b')' | b']' | b'}' ) && self.state == State::Normal && n_brackets > 0 && n_brackets == self.n_opened_brackets {
+                    self.indents.pop();
+
+                    Some( TokenKind::Dedent )
+
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn get_line_indent_kind( &mut self, pos: usize ) -> Option< TokenKind > {
+        let width = self.indent_width_at( pos );
+        let ( top, n_brackets ) = *self.indents.last().unwrap();
+
+        if width > top && self.after_colon {
+            let step = width - top;
+            match self.indent_width {
+                Some( expected ) if expected != step => {
+                    self.error( self.line_start, pos, LexerErrorKind::InconsistentIndent { expected, found: step } );
+                }
+                Some( _ ) => {},
+                None => {
+                    self.indent_width = Some( step );
+                }
+            }
+            self.indents.push( ( width, self.n_opened_brackets ) );
+
+            Some( TokenKind::Indent )
+
+        } else if width < top && n_brackets == self.n_opened_brackets {
+            let n = self.pop_indents( width );
+            let ( top, n_brackets ) = *self.indents.last().unwrap();
+            if top != width && n_brackets == self.n_opened_brackets {
+                self.error( self.line_start, pos, LexerErrorKind::UnknownDedentLevel( width ) );
+            }
+            self.pending_dedents = n - 1;
+
+            Some( TokenKind::Dedent )
+
+        } else {
+            None
+        }
+    }
+
+    //  Whether only spaces and comments follow `pos` up to the end of line. A block comment
+    //  can span several lines, the indentation is then measured by the line it starts on:
+    //
+    //      /* a
+    //      */ x
+    //
+    fn is_comment_only( &self, pos: usize ) -> bool {
+        let mut i = pos;
+
+        loop {
+            match self.at( i ) {
+                None | Some( b'\r' | b'\n' ) => break true,
+                Some( b ) if is_space( b ) => { i += 1; },
+                Some( b'/' ) if self.byte_is( i + 1, b'/' ) => break true,
+                Some( b'/' ) if self.byte_is( i + 1, b'*' ) => match self.closed_block_comment_end( i ) {
+                    Some( end ) => { i = end; },
+                    None => break true,
+                },
+                Some( _ ) => break false,
+            }
+        }
+    }
+
+    //  Closes the levels wider than `width` opened inside the current brackets, returns their number.
+    //
+    fn pop_indents( &mut self, width: usize ) -> usize {
+        let mut n = 0;
+        while matches!( self.indents.last(), Some( &( top, n_brackets ) ) if top > width && n_brackets == self.n_opened_brackets ) {
+            self.indents.pop();
+            n += 1;
+        }
+
+        n
+    }
+
+    //  Trivia and Dedent do not change what the last token is.
+    //
+    fn track_colon( &mut self, kind: TokenKind ) {
+        match kind {
+            T![ ] | TokenKind::EOL | TokenKind::Comment | TokenKind::BlockComment | TokenKind::DocComment | TokenKind::InnerDocComment | TokenKind::Dedent => {},
+            _ => {
+                self.after_colon = kind == T![ : ];
+            }
+        }
+    }
+
+    //  Digits, prefixes and suffixes are checked later by `validation::validate` with `number::parse_number`.
+    //  Here we only find where the number ends:
+    //
+    //      0x1F  0b1010_u8  1_000  1.5  1e-10  2.5f32
+    //
+    fn number_end( &self, pos: usize ) -> usize {
+        let mut i = pos + 1;
+
+        let has_prefix = self.byte_is( pos, b'0' ) && self.byte_matches( pos + 1, | b | matches!( b, b'x' | b'X' | b'o' | b'O' | b'b' | b'B' ) );
+
+        //  `t.0.1` is a field of a tuple field, not `t.` and `0.1`. But `0..1.5` is a range.
+        let is_tuple_field = pos > 0 && self.byte_is( pos - 1, b'.' ) && !( pos > 1 && self.byte_is( pos - 2, b'.' ) );
+
+        if is_tuple_field {
+            i = self.iterate_while( i, is_digit_or_underscore );
+
+        } else if !has_prefix {
+            i = self.iterate_while( i, is_digit_or_underscore );
+
+            //  `1..2` and `1.foo()` are not fractions.
+            if self.byte_is( i, b'.' ) && self.byte_matches( i + 1, is_digit ) {
+                i = self.iterate_while( i + 1, is_digit_or_underscore );
+            }
+
+            if self.byte_matches( i, | b | b == b'e' || b == b'E' ) {
+                let j = if self.byte_matches( i + 1, | b | b == b'+' || b == b'-' ) { i + 2 } else { i + 1 };
+                if self.byte_matches( j, is_digit ) {
+                    i = j;
+                }
+            }
+        }
+
+        //  Rest of digits and a type suffix.
+        self.iterate_while( i, is_id_next )
+    }
+
+    fn at_triple_quote( &self, pos: usize ) -> bool {
+        self.byte_is( pos, b'"' ) && self.byte_is( pos + 1, b'"' ) && self.byte_is( pos + 2, b'"' )
+    }
+
+    fn quote_kind_of( &self, start: usize, end: usize ) -> TokenKind {
+        if end - start == 3 { T![ "\"\"\"" ] } else { T![ '"' ] }
+    }
+
+    //  `pos` is right after the opening `"`.
+    //
+    fn open_quote( &mut self, pos: usize ) -> ( TokenKind, usize ) {
+        self.string_starts.push( pos - 1 );
+
+        if self.byte_is( pos, b'"' ) && self.byte_is( pos + 1, b'"' ) {
+            self.quote = Quote::Triple;
+
+            ( T![ "\"\"\"" ], pos + 2 )
+
+        } else {
+            self.quote = Quote::Single;
+
+            ( T![ '"' ], pos )
+        }
+    }
+
+    //  Returns to the `${ }` of the enclosing string, if any.
+    //
+    fn close_string( &mut self ) {
+        self.string_starts.pop();
+
+        match self.opened_quotes.pop() {
+            Some( ( n_opened_curlies, quote ) ) => {
+                self.n_opened_curlies = n_opened_curlies;
+                self.quote = quote;
+                self.state = State::StringExpr;
+            }
+            None => {
+                self.quote = Quote::Single;
+                self.state = State::Normal;
+            }
+        }
+    }
+
+    //  Closes the innermost string which is unterminated at `pos`.
+    //
+    fn unterminated_string( &mut self, pos: usize ) {
+        let start = self.string_starts.last().copied().unwrap_or( pos );
+        self.error( start, pos, LexerErrorKind::UnterminatedString );
+
+        self.close_string();
+    }
+
+    fn unknown( &mut self, pos: usize ) -> ( TokenKind, usize ) {
+        let end = self.error_end( pos );
+        self.error( pos, end, LexerErrorKind::UnknownChars );
+
+        ( TokenKind::Unknown, end )
+    }
+
+    //  `:` at `pos` starts a format spec if it is followed by `[[fill]align]` and format chars up to `}`:
+    //
+    //      ${ x:>10.2 }  ${ x:*^5 }
+    //
+    //  So `:` of e.g. a closure `${ f( | x: u8 | x ) }` is not a format spec.
+    //
+    fn format_spec_end( &self, pos: usize ) -> Option< usize > {
+        let rest = &self.input[ pos + 1 .. ];
+
+        let mut start = 0;
+        let mut chars = rest.char_indices();
+        if let ( Some( ( _, fill ) ), Some( ( i, align ) ) ) = ( chars.next(), chars.next() ) {
+            if !matches!( fill, '{' | '}' | '"' | '\n' | '\r' ) && matches!( align, '<' | '^' | '>' ) {
+                start = i + 1;
+            }
+        }
+
+        let end = rest[ start .. ]
+            .find( | c: char | !( c.is_ascii_alphanumeric() || matches!( c, '+' | '-' | '#' | '.' | '<' | '^' | '>' ) ) )
+            .map_or( rest.len(), | i | start + i );
+
+        if rest[ end .. ].trim_start_matches( [ ' ', '\t' ] ).starts_with( '}' ) {
+            Some( pos + 1 + end )
+
+        } else {
+            None
+        }
+    }
+
+    //  Raw strings have no escapes and interpolations and can span lines:
+    //
+    //      r"C:\path"  r#"say "hi""#
+    //
+    fn at_raw_string( &self, pos: usize ) -> bool {
+        let i = self.iterate_while( pos, | b | b == b'#' );
+
+        self.byte_is( i, b'"' )
+    }
+
+    fn raw_string_end( &mut self, pos: usize ) -> usize {
+        let n_hashes = self.iterate_while( pos + 1, | b | b == b'#' ) - pos - 1;
+        let mut i = pos + 1 + n_hashes + 1;
+
+        loop {
+            match self.at( i ) {
+                Some( b'"' ) => {
+                    i += 1;
+                    if self.iterate_while( i, | b | b == b'#' ) - i >= n_hashes {
+                        break i + n_hashes;
+                    }
+                }
+                Some( _ ) => { i += 1; }
+                None => {
+                    self.error( pos, i, LexerErrorKind::UnterminatedRawString );
+                    break i;
+                }
+            }
+        }
+    }
+
+    //  Block comments nest:
+    //
+    //      /* a /* b */ c */
+    //
+    fn block_comment_end( &mut self, pos: usize ) -> usize {
+        self.closed_block_comment_end( pos ).unwrap_or_else( || {
+            self.error( pos, self.bytes.len(), LexerErrorKind::UnterminatedBlockComment );
+            self.bytes.len()
+        } )
+    }
+
+    //  None if the comment is not closed.
+    //
+    fn closed_block_comment_end( &self, pos: usize ) -> Option< usize > {
+        let mut i = pos + 2;
+        let mut depth = 1;
+
+        loop {
+            let offset = memchr2( b'*', b'/', &self.bytes[ i .. ] )?;
+            i += offset;
+
+            match ( self.bytes[ i ], self.at( i + 1 ) ) {
+                ( b'*', Some( b'/' ) ) => {
+                    i += 2;
+                    depth -= 1;
+                    if depth == 0 {
+                        break Some( i );
+                    }
+                }
+                ( b'/', Some( b'*' ) ) => {
+                    i += 2;
+                    depth += 1;
+                }
+                _ => { i += 1; }
+            }
+        }
+    }
+
+    //  Returns the end of the string fragment starting at `pos` and whether it has escapes.
+    //  The fragment ends at the closing quote, at `${` or at the end of line of a single line string.
+    //
+    fn string_fragment_end( &self, pos: usize ) -> ( usize, bool ) {
+        let limit = match self.quote {
+            Quote::Single => memchr( b'\n', &self.bytes[ pos .. ] ).map_or( self.bytes.len(), | i | pos + i ),
+            Quote::Triple => self.bytes.len(),
+        };
+
+        let mut i = pos;
+        let mut has_escapes = false;
+
+        while let Some( offset ) = memchr3( b'"', b'$', b'\\', &self.bytes[ i .. limit ] ) {
+            i += offset;
+
+            match self.bytes[ i ] {
+                b'"' if self.quote == Quote::Single || self.at_triple_quote( i ) => { return ( i, has_escapes ); }
+                b'$' if self.byte_is( i + 1, b'{' ) => { return ( i, has_escapes ); }
+                b'\\' => {
+                    has_escapes = true;
+                    //  Escaped `"`, `$` and `\\` never end the fragment.
+                    i += if self.byte_matches( i + 1, | b | b != b'\n' && b != b'\r' ) { 2 } else { 1 };
+                }
+                _ => { i += 1; }
+            }
+        }
+
+        //  `\r` of `\r\n` is a part of EOL.
+        let end = if limit > i && self.bytes[ limit - 1 ] == b'\r' && limit < self.bytes.len() { limit - 1 } else { limit };
+
+        ( end, has_escapes )
+    }
+
     fn get_token_kind( &mut self ) -> ( TokenKind, usize ) {
         let bytes = self.bytes;
         let pos = self.pos;
 
+        if self.track_indents {
+            if let Some( kind ) = self.get_indent_kind() {
+                return ( kind, pos );
+            }
+        }
+
         if pos >= bytes.len() {
-            return ( TokenKind::None, pos );
+            while self.state != State::Normal {
+                self.unterminated_string( pos );
+            }
+
+            ( TokenKind::None, pos )
 
         } else {
             let b = bytes.get( pos ).unwrap();
@@ -100,37 +600,34 @@ impl < 'a > Lexer < 'a >{
 
                     self.n_opened_curlies = 0;
                     self.opened_quotes.clear();
+                    self.string_starts.clear();
 
-                    return ( T![ '"' ], i );
+                    return self.open_quote( i );
                 }
 
                 ( b'"', State::StringExpr ) => {
                     self.state = State::StringFragment;
-                    self.opened_quotes.push( self.n_opened_curlies );
+                    self.opened_quotes.push( ( self.n_opened_curlies, self.quote ) );
                     self.n_opened_curlies = 0;
 
-                    return ( T![ '"' ], i );
+                    return self.open_quote( i );
                 }
 
-                ( b'"', State::StringFragment ) => {
-                    if self.opened_quotes.is_empty() {
-                        self.state = State::Normal;
+                ( b'"', State::StringFragment ) if self.quote == Quote::Single || self.at_triple_quote( pos ) => {
+                    let end = match self.quote {
+                        Quote::Single => i,
+                        Quote::Triple => i + 2,
+                    };
+                    self.close_string();
 
-                    } else {
-                        self.n_opened_curlies = self.opened_quotes.pop().unwrap();
-                        self.state = State::StringExpr;
-                    }
-
-                    return ( T![ '"' ], i );
+                    return ( self.quote_kind_of( pos, end ), end );
                 }
 
-                ( b'$', State::StringFragment ) => {
-                    if self.byte_is( i , b'{' ) {
-                        self.state = State::StringExpr;
-                        self.n_opened_curlies = 1;
+                ( b'$', State::StringFragment ) if self.byte_is( i , b'{' ) => {
+                    self.state = State::StringExpr;
+                    self.n_opened_curlies = 1;
 
-                        return ( T![ "${" ], i + 1 );
-                    }
+                    return ( T![ "${" ], i + 1 );
                 }
 
                 ( b'{', State::StringExpr ) => {
@@ -139,8 +636,21 @@ impl < 'a > Lexer < 'a >{
                     return ( T![ '{' ], i );
                 }
 
+                ( b':', State::StringExpr ) if self.n_opened_curlies == 1 => {
+                    if let Some( end ) = self.format_spec_end( pos ) {
+                        if let Err( error ) = parse_format_spec( &self.input[ i .. end ] ) {
+                            self.error( pos, end, LexerErrorKind::InvalidFormatSpec( error ) );
+                        }
+
+                        return ( TokenKind::FormatSpec, end );
+                    }
+                }
+
                 ( b'}', State::StringExpr ) => {
-                    self.n_opened_curlies -= 1;
+                    match self.n_opened_curlies.checked_sub( 1 ) {
+                        Some( n ) => self.n_opened_curlies = n,
+                        None => self.error( pos, i, LexerErrorKind::UnbalancedCurly ),
+                    }
                     if self.n_opened_curlies == 0 {
                         self.state = State::StringFragment;
                     }
@@ -148,26 +658,32 @@ impl < 'a > Lexer < 'a >{
                     return ( T![ '}' ], i );
                 }
 
-                ( b'\n', _ ) => {
-                    self.state = State::Normal;
+                //  Newlines are part of `"""` strings, including their `${ }`.
+                ( b'\n' | b'\r', _ ) if self.eol_end( pos ).is_some() && !( self.state == State::StringFragment && self.quote == Quote::Triple ) => {
+                    let i = self.eol_end( pos ).unwrap();
+
+                    //  Unterminated single line strings end here.
+                    while self.state != State::Normal && self.quote == Quote::Single {
+                        self.unterminated_string( pos );
+                    }
+
+                    if self.state == State::Normal {
+                        self.at_line_start = true;
+                        self.line_start = i;
+                    }
 
                     return ( TokenKind::EOL, i );
                 }
 
                 ( _, State::StringFragment ) => {
-                    while let Some( &b ) = self.bytes.get( i ) {
-                        match b {
-                            b'\n' | b'"' => { break; }
-                            b'$' => {
-                                if self.byte_is( i + 1, b'{' ) {
-                                    break;
-
-                                } else {
-                                    i += 1;
-                                }
-                            }
-                            _ => { i += 1; }
-                        }
+                    let ( i, has_escapes ) = self.string_fragment_end( pos );
+
+                    if has_escapes {
+                        let mut errors = Vec::new();
+                        unescape( &self.input[ pos .. i ], | start, end, kind | {
+                            errors.push( LexerError::new( pos + start, pos + end, kind ) );
+                        } );
+                        self.errors.append( &mut errors );
                     }
 
                     return ( TokenKind::StringFragment, i );
@@ -177,43 +693,55 @@ impl < 'a > Lexer < 'a >{
             }
 
             match b {
-                b' ' => ( T![ ], self.iterate_while( i, is_space ) ),
+                b' ' | b'\t' | 0x0c | b'\r' => ( T![ ], self.iterate_while( i, is_space ) ),
 
                 b'/' => {
                     match self.bytes.get( i ) {
                         Some( b'/' ) => {
-                            i += 1;
-                            loop {
-                                match self.bytes.get( i ) {
-                                    Some( b'\n' ) | None => { break; }
-                                    _ => { i += 1 },
-                                }
-                            }
-                            ( TokenKind::Comment, i )
+                            //  `///` and `//!` are doc comments, but `////` is not.
+                            let kind = match ( self.at( i + 1 ), self.at( i + 2 ) ) {
+                                ( Some( b'/' ), Some( b'/' ) ) => TokenKind::Comment,
+                                ( Some( b'/' ), _ ) => TokenKind::DocComment,
+                                ( Some( b'!' ), _ ) => TokenKind::InnerDocComment,
+                                _ => TokenKind::Comment,
+                            };
+
+                            let end = match memchr( b'\n', &self.bytes[ i .. ] ) {
+                                Some( offset ) if self.bytes[ i + offset - 1 ] == b'\r' => i + offset - 1,
+                                Some( offset ) => i + offset,
+                                None => self.bytes.len(),
+                            };
+                            ( kind, end )
                         }
+                        Some( b'*' ) => ( TokenKind::BlockComment, self.block_comment_end( pos ) ),
                         Some( b'=' ) => ( T![ /= ], i + 1 ),
                         _ => ( T![ / ], i ),
                     }
                 }
 
+                b'r' if self.at_raw_string( i ) => ( TokenKind::RawString, self.raw_string_end( pos ) ),
+
                 b'A' ..= b'Z' |
                 b'a' ..= b'z' |
                 b'_' => {
-                    i = self.iterate_while( i, is_id_next );
+                    i = self.id_end( i );
 
                     ( id_or_keyword( self.bytes.get(  pos .. i ).unwrap() ), i )
                 }
 
-                b'0' ..= b'9' => {
-                    let i = self.iterate_while( i, is_digit );
+                0x80 .. => {
+                    let c = self.char_at( pos );
+
+                    if is_xid_start( c ) {
+                        ( TokenKind::Id, self.id_end( pos + c.len_utf8() ) )
 
-                    if self.byte_is( i, b'.' ) && self.byte_matches( i, is_digit ) {
-                        ( TokenKind::Number, self.iterate_while( i + 1, is_digit ) )
                     } else {
-                        ( TokenKind::Number, i )
+                        self.unknown( pos )
                     }
                 }
 
+                b'0' ..= b'9' => ( TokenKind::Number, self.number_end( pos ) ),
+
                 b'+' => {
                     if self.byte_is( i, b'=' ) {
                         ( T![ += ], i + 1 )
@@ -230,18 +758,6 @@ impl < 'a > Lexer < 'a >{
                     }
                 }
 
-                // b'-' => {
-                //     if self.byte_is( i, b'=' ) {
-                //         ( T![ -= ], i + 1 )
-
-                //     } else if self.byte_is( i, b'>' ) {
-                //         ( T![ -> ], i + 1 )
-
-                //     } else {
-                //         ( T![ - ], i )
-                //     }
-                // }
-
                 b'*' => {
                     if self.byte_is( i, b'=' ) {
                         ( T![ *= ], i + 1 )
@@ -261,6 +777,8 @@ impl < 'a > Lexer < 'a >{
                 b'=' => {
                     if self.byte_is( i, b'=' ) {
                         ( T![ == ], i + 1 )
+                    } else if self.byte_is( i, b'>' ) {
+                        ( T![ => ], i + 1 )
                     } else {
                         ( T![ = ], i )
                     }
@@ -304,18 +822,6 @@ impl < 'a > Lexer < 'a >{
                         }
                         _ => ( T![ | ], i ),
                     }
-
-                    // if self.byte_is( i, b'|' ) {
-                    //     if self.byte_is( i + 1, b'=' ) {
-                    //         ( T![ ||= ], i + 2 )
-
-                    //     } else {
-                    //         ( T![ || ], i + 1 )
-                    //     }
-
-                    // } else {
-                    //     ( T![ | ], i )
-                    // }
                 }
 
                 b'!' => {
@@ -341,12 +847,25 @@ impl < 'a > Lexer < 'a >{
                     }
                 }
 
-                b'(' => ( T![ '(' ], i ),
-                b')' => ( T![ ')' ], i ),
-                b'[' => ( T![ '[' ], i ),
-                b']' => ( T![ ']' ], i ),
-                b'{' => ( T![ '{' ], i ),
-                b'}' => ( T![ '}' ], i ),
+                b'(' | b'[' | b'{' => {
+                    self.n_opened_brackets += 1;
+
+                    match b {
+                        b'(' => ( T![ '(' ], i ),
+                        b'[' => ( T![ '[' ], i ),
+                        _ => ( T![ '{' ], i ),
+                    }
+                }
+
+                b')' | b']' | b'}' => {
+                    self.n_opened_brackets = self.n_opened_brackets.saturating_sub( 1 );
+
+                    match b {
+                        b')' => ( T![ ')' ], i ),
+                        b']' => ( T![ ']' ], i ),
+                        _ => ( T![ '}' ], i ),
+                    }
+                }
                 b':' => ( T![ : ], i ),
                 b';' => ( T![ ; ], i ),
                 b',' => ( T![ , ], i ),
@@ -355,15 +874,235 @@ impl < 'a > Lexer < 'a >{
                 b'^' => ( T![ ^ ], i ),
                 b'~' => ( T![ ~ ], i ),
                 b'?' => ( T![ ? ], i ),
+                b'\'' if self.byte_matches( i, | b | b == b'_' || b.is_ascii_alphabetic() ) => ( TokenKind::Label, self.id_end( i ) ),
                 b'\'' => ( T![ '\'' ], i ),
                 b'`' => ( T![ '`' ], i ),
                 b'$' => ( T![ $ ], i ),
                 b'\\' => ( T![ '\\' ], i ),
 
-                _ => ( TokenKind::Unknown, self.iterate_while( i, is_error ) ),
+                _ => self.unknown( pos ),
+            }
+        }
+    }
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+//  Result of `Lexer::relex`: tokens `old` of the previous stream were replaced with tokens `new`.
+//  Tokens before `old.start` are the same, tokens after `old.end` are only shifted.
+//
+#[ derive( Debug, Clone, PartialEq ) ]
+pub struct TokenEdit {
+    pub old: Range< usize >,
+    pub new: Range< usize >,
+}
+
+//  Tokens and errors of the whole input with the lexer states at line starts,
+//  so it can be relexed after an edit from the line of the edit.
+//
+pub struct Lexed< 'a > {
+    pub tokens: Vec< Token< 'a > >,
+    //  In the order they are reported.
+    pub errors: Vec< LexerError >,
+    //  The input start and every line after an EOL token.
+    lines: Vec< LineState >,
+}
+
+//  State of the lexer before the `token`-th token at `pos`, with the number of errors reported before.
+//
+#[ derive( Clone, PartialEq, Debug ) ]
+struct LineState {
+    token: usize,
+    pos: usize,
+    n_errors: usize,
+    snapshot: Snapshot,
+}
+
+//  Everything besides the input and the position the lexing depends on.
+//
+#[ derive( Clone, PartialEq, Debug ) ]
+struct Snapshot {
+    state: State,
+    n_opened_curlies: u32,
+    quote: Quote,
+    opened_quotes: Vec< ( u32, Quote ) >,
+    string_starts: Vec< usize >,
+    indents: Vec< ( usize, u32 ) >,
+    indent_width: Option< usize >,
+    pending_dedents: usize,
+    at_line_start: bool,
+    after_colon: bool,
+    line_start: usize,
+    n_opened_brackets: u32,
+}
+
+impl Snapshot {
+
+    fn shifted( &self, shift: impl Fn( usize ) -> usize ) -> Self {
+        Snapshot {
+            string_starts: self.string_starts.iter().map( | &pos | shift( pos ) ).collect(),
+            line_start: shift( self.line_start ),
+            ..self.clone()
+        }
+    }
+
+}
+
+impl < 'a > Lexer < 'a > {
+
+    pub fn lex( mut self ) -> Lexed< 'a > {
+        let mut tokens = Vec::new();
+        let mut lines = vec![ self.line_state( 0 ) ];
+
+        while let Some( token ) = self.next() {
+            tokens.push( token );
+            if token.kind == TokenKind::EOL {
+                lines.push( self.line_state( tokens.len() ) );
             }
         }
+
+        Lexed {
+            tokens,
+            errors: self.errors,
+            lines,
+        }
+    }
+
+    //  Lexes `self.input` which is the previous input with `edit` range replaced by `replacement`.
+    //  `old` is the previous input lexed with the same options (i.e. `with_indents` or not).
+    //
+    //  Lexing restarts from the saved state at the start of the line containing the edit
+    //  and stops at the first line after the edit which starts in the same state as before.
+    //  The rest of tokens, errors and line states are taken from `old` and shifted.
+    //
+    pub fn relex( mut self, old: &Lexed, edit: Range< usize >, replacement: &str ) -> ( Lexed< 'a >, TokenEdit ) {
+        debug_assert_eq!( self.input.get( edit.start .. edit.start + replacement.len() ), Some( replacement ) );
+
+        let new_edit_end = edit.start + replacement.len();
+        let shift = | pos: usize | if pos >= edit.end { pos + new_edit_end - edit.end } else { pos };
+
+        let start = old.tokens.partition_point( | t | t.end < edit.start );
+        let restart = old.lines.partition_point( | line | line.token <= start ) - 1;
+        let line = &old.lines[ restart ];
+
+        self.pos = line.pos;
+        self.restore( &line.snapshot );
+        self.errors = old.errors[ .. line.n_errors ].to_vec();
+
+        let mut tokens: Vec< Token< 'a > > = old.tokens[ .. line.token ]
+            .iter()
+            .map( | t | self.token( t.kind, t.start, t.end ) )
+            .collect();
+        let mut lines = old.lines[ ..= restart ].to_vec();
+
+        //  Old lines which can be at the same position after the edit.
+        let after = old.lines.partition_point( | line | line.pos < edit.end );
+        let mut synced = None;
+
+        while let Some( token ) = self.next() {
+            tokens.push( token );
+            if token.kind != TokenKind::EOL {
+                continue;
+            }
+
+            let line = self.line_state( tokens.len() );
+            if self.pos >= new_edit_end {
+                if let Ok( i ) = old.lines[ after .. ].binary_search_by_key( &self.pos, | line | shift( line.pos ) ) {
+                    if old.lines[ after + i ].snapshot.shifted( shift ) == line.snapshot {
+                        synced = Some( after + i );
+                    }
+                }
+            }
+            lines.push( line );
+
+            if synced.is_some() {
+                break;
+            }
+        }
+
+        let new_end = tokens.len();
+        let old_end = synced.map_or( old.tokens.len(), | i | old.lines[ i ].token );
+
+        if let Some( i ) = synced {
+            let ( old_errors, new_errors ) = ( old.lines[ i ].n_errors, self.errors.len() );
+
+            tokens.extend( old.tokens[ old_end .. ]
+                .iter()
+                .map( | t | self.token( t.kind, shift( t.start ), shift( t.end ) ) )
+            );
+            self.errors.extend( old.errors[ old_errors .. ]
+                .iter()
+                .map( | e | LexerError::new( shift( e.start ), shift( e.end ), e.kind.clone() ) )
+            );
+            lines.extend( old.lines[ i + 1 .. ]
+                .iter()
+                .map( | line | LineState {
+                    token: line.token + new_end - old_end,
+                    pos: shift( line.pos ),
+                    n_errors: line.n_errors + new_errors - old_errors,
+                    snapshot: line.snapshot.shifted( shift ),
+                } )
+            );
+        }
+
+        ( Lexed { tokens, errors: self.errors, lines }, TokenEdit {
+            old: line.token .. old_end,
+            new: line.token .. new_end,
+        } )
+    }
+
+    fn token( &self, kind: TokenKind, start: usize, end: usize ) -> Token< 'a > {
+        Token {
+            kind,
+            text: &self.input[ start .. end ],
+            start,
+            end,
+        }
+    }
+
+    fn line_state( &self, token: usize ) -> LineState {
+        LineState {
+            token,
+            pos: self.pos,
+            n_errors: self.errors.len(),
+            snapshot: self.snapshot(),
+        }
     }
+
+    fn snapshot( &self ) -> Snapshot {
+        Snapshot {
+            state: self.state,
+            n_opened_curlies: self.n_opened_curlies,
+            quote: self.quote,
+            opened_quotes: self.opened_quotes.clone(),
+            string_starts: self.string_starts.clone(),
+            indents: self.indents.clone(),
+            indent_width: self.indent_width,
+            pending_dedents: self.pending_dedents,
+            at_line_start: self.at_line_start,
+            after_colon: self.after_colon,
+            line_start: self.line_start,
+            n_opened_brackets: self.n_opened_brackets,
+        }
+    }
+
+    fn restore( &mut self, snapshot: &Snapshot ) {
+        let snapshot = snapshot.clone();
+
+        self.state = snapshot.state;
+        self.n_opened_curlies = snapshot.n_opened_curlies;
+        self.quote = snapshot.quote;
+        self.opened_quotes = snapshot.opened_quotes;
+        self.string_starts = snapshot.string_starts;
+        self.indents = snapshot.indents;
+        self.indent_width = snapshot.indent_width;
+        self.pending_dedents = snapshot.pending_dedents;
+        self.at_line_start = snapshot.at_line_start;
+        self.after_colon = snapshot.after_colon;
+        self.line_start = snapshot.line_start;
+        self.n_opened_brackets = snapshot.n_opened_brackets;
+    }
+
 }
 
 impl < 'a > Iterator for Lexer < 'a > {
@@ -378,6 +1117,9 @@ impl < 'a > Iterator for Lexer < 'a > {
         }
 
         self.pos = end;
+        if self.track_indents {
+            self.track_colon( kind );
+        }
 
         Some( Token {
             kind,
@@ -391,7 +1133,7 @@ impl < 'a > Iterator for Lexer < 'a > {
 
 #[inline]
 fn is_space( b: u8 ) -> bool {
-    b == b' '
+    b == b' ' || b == b'\t' || b == 0x0c
 }
 
 #[inline]
@@ -405,8 +1147,13 @@ fn is_digit( b: u8 ) -> bool {
 }
 
 #[inline]
-fn is_error( b: u8 ) -> bool {
-    b != b'\n' && ( b < b' ' || b > b'~' )
+fn is_digit_or_underscore( b: u8 ) -> bool {
+    b.is_ascii_digit() || b == b'_'
+}
+
+#[inline]
+fn is_error( c: char ) -> bool {
+    !matches!( c, '\t' | '\n' | '\x0c' | '\r' ) && !( ' ' ..= '~' ).contains( &c ) && !is_xid_start( c )
 }
 
 fn id_or_keyword( id: &[ u8 ] ) -> TokenKind {
@@ -420,6 +1167,373 @@ fn id_or_keyword( id: &[ u8 ] ) -> TokenKind {
         b"for" => T![ for ],
         b"while" => T![ while ],
         b"fn" => T![ fn ],
+        b"else" => T![ else ],
+        b"return" => T![ return ],
+        b"break" => T![ break ],
+        b"continue" => T![ continue ],
+        b"match" => T![ match ],
+        b"in" => T![ in ],
+        b"true" => T![ true ],
+        b"false" => T![ false ],
+        b"null" => T![ null ],
+        b"import" => T![ import ],
+        b"export" => T![ export ],
+        b"pub" => T![ pub ],
+        b"impl" => T![ impl ],
+        b"trait" => T![ trait ],
+        b"as" => T![ as ],
+        b"_" => T![ _ ],
         _ => TokenKind::Id,
     }
 }
+
+#[ cfg( test ) ]
+mod tests {
+    use super::*;
+    use crate::format_spec::FormatSpecError;
+
+    fn kinds( lexer: Lexer ) -> Vec< TokenKind > {
+        lexer
+            .filter( | t | t.kind != T![ ] && t.kind != TokenKind::EOL )
+            .map( | t | t.kind )
+            .collect()
+    }
+
+    #[ test ]
+    fn indents() {
+        let input = "fn f():\n    if x:\n        y\n\n    // comment\n    z\nfn g";
+
+        assert_eq!( kinds( Lexer::with_indents( input ) ), vec![
+            T![ fn ], TokenKind::Id, T![ '(' ], T![ ')' ], T![ : ],
+            TokenKind::Indent, T![ if ], TokenKind::Id, T![ : ],
+            TokenKind::Indent, TokenKind::Id,
+            TokenKind::Comment,
+            TokenKind::Dedent, TokenKind::Id,
+            TokenKind::Dedent, T![ fn ], TokenKind::Id,
+        ] );
+
+        //  Block comment lines do not change the indentation either, unless code follows the comment.
+        let input = "a:\n/* x */ /* y\n*/\n    b\n  /* z */\n    c\n/* w */ d";
+        assert_eq!( kinds( Lexer::with_indents( input ) ), vec![
+            TokenKind::Id, T![ : ],
+            TokenKind::BlockComment, TokenKind::BlockComment,
+            TokenKind::Indent, TokenKind::Id,
+            TokenKind::BlockComment,
+            TokenKind::Id,
+            TokenKind::Dedent, TokenKind::BlockComment, TokenKind::Id,
+        ] );
+
+        assert_eq!( kinds( Lexer::with_indents( "a:\n  b:\n    c" ) ), vec![
+            TokenKind::Id, T![ : ],
+            TokenKind::Indent, TokenKind::Id, T![ : ],
+            TokenKind::Indent, TokenKind::Id,
+            TokenKind::Dedent, TokenKind::Dedent,
+        ] );
+
+        //  No indents for continuation lines and without `with_indents`.
+        assert_eq!( kinds( Lexer::with_indents( "f(\n    x\n)" ) ), vec![
+            TokenKind::Id, T![ '(' ], TokenKind::Id, T![ ')' ],
+        ] );
+        assert_eq!( kinds( Lexer::with_indents( "type X =\n    number\nfn g() {}" ) ), vec![
+            T![ type ], TokenKind::Id, T![ = ], TokenKind::Id,
+            T![ fn ], TokenKind::Id, T![ '(' ], T![ ')' ], T![ '{' ], T![ '}' ],
+        ] );
+        assert_eq!( kinds( Lexer::with_indents( "a:\n    b +\n        c\n    d" ) ), vec![
+            TokenKind::Id, T![ : ],
+            TokenKind::Indent, TokenKind::Id, T![ + ], TokenKind::Id,
+            TokenKind::Id,
+            TokenKind::Dedent,
+        ] );
+        assert_eq!( kinds( Lexer::new( "a:\n    b" ) ), vec![
+            TokenKind::Id, T![ : ], TokenKind::Id,
+        ] );
+    }
+
+    #[ test ]
+    fn indents_in_brackets() {
+        //  The block is closed by a less indented line or by the closing bracket.
+        assert_eq!( kinds( Lexer::with_indents( "fn f() {\n    if x:\n        y\n}" ) ), vec![
+            T![ fn ], TokenKind::Id, T![ '(' ], T![ ')' ], T![ '{' ],
+            T![ if ], TokenKind::Id, T![ : ],
+            TokenKind::Indent, TokenKind::Id,
+            TokenKind::Dedent, T![ '}' ],
+        ] );
+        assert_eq!( kinds( Lexer::with_indents( "g( fn():\n    x )\ny" ) ), vec![
+            TokenKind::Id, T![ '(' ], T![ fn ], T![ '(' ], T![ ')' ], T![ : ],
+            TokenKind::Indent, TokenKind::Id,
+            TokenKind::Dedent, T![ ')' ],
+            TokenKind::Id,
+        ] );
+
+        //  Lines inside brackets opened in the block are continuation lines.
+        assert_eq!( kinds( Lexer::with_indents( "a:\n    f(\n  x )\n    b" ) ), vec![
+            TokenKind::Id, T![ : ],
+            TokenKind::Indent, TokenKind::Id, T![ '(' ], TokenKind::Id, T![ ')' ],
+            TokenKind::Id,
+            TokenKind::Dedent,
+        ] );
+    }
+
+    #[ test ]
+    fn indent_errors() {
+        let mut lexer = Lexer::with_indents( "a:\n    b:\n      c\n  d" );
+        let tokens: Vec< Token > = lexer.by_ref().collect();
+        assert!( tokens.iter().all( | t | t.kind != TokenKind::Unknown ) );
+
+        let errors: Vec< &LexerErrorKind > = lexer.errors().iter().map( | e | &e.kind ).collect();
+        assert_eq!( errors, vec![
+            &LexerErrorKind::InconsistentIndent { expected: 4, found: 2 },
+            &LexerErrorKind::UnknownDedentLevel( 2 ),
+        ] );
+        assert_eq!( ( lexer.errors()[ 1 ].start, lexer.errors()[ 1 ].end ), ( 18, 20 ) );
+    }
+
+    #[ test ]
+    fn string_escapes() {
+        let input = r#""a\"b ${ x } \${y}\q""#;
+        let mut lexer = Lexer::new( input );
+        let tokens: Vec< ( TokenKind, &str ) > = lexer.by_ref().map( | t | ( t.kind, t.text ) ).collect();
+
+        assert_eq!( tokens, vec![
+            ( T![ '"' ], "\"" ),
+            ( TokenKind::StringFragment, r#"a\"b "# ),
+            ( T![ "${" ], "${" ),
+            ( T![ ], " " ),
+            ( TokenKind::Id, "x" ),
+            ( T![ ], " " ),
+            ( T![ '}' ], "}" ),
+            ( TokenKind::StringFragment, r" \${y}\q" ),
+            ( T![ '"' ], "\"" ),
+        ] );
+
+        let errors: Vec< ( usize, usize, &LexerErrorKind ) > = lexer.errors().iter().map( | e | ( e.start, e.end, &e.kind ) ).collect();
+        assert_eq!( errors, vec![ ( 18, 20, &LexerErrorKind::InvalidEscape( Some( 'q' ) ) ) ] );
+    }
+
+    #[ test ]
+    fn whitespace() {
+        let input = "fn f():\r\n\tx\t// c\r\n\r\n\t\x0cy \"s\r\n    \"\"\"\r\n\"\"\"\r\nz\rw";
+        let mut lexer = Lexer::with_indents( input );
+        let texts: Vec< ( TokenKind, &str ) > = lexer
+            .by_ref()
+            .map( | t | ( t.kind, t.text ) )
+            .collect();
+
+        assert_eq!( texts, vec![
+            ( T![ fn ], "fn" ), ( T![ ], " " ), ( TokenKind::Id, "f" ), ( T![ '(' ], "(" ), ( T![ ')' ], ")" ), ( T![ : ], ":" ),
+            ( TokenKind::EOL, "\r\n" ),
+            ( T![ ], "\t" ), ( TokenKind::Indent, "" ), ( TokenKind::Id, "x" ), ( T![ ], "\t" ), ( TokenKind::Comment, "// c" ),
+            ( TokenKind::EOL, "\r\n" ),
+            ( TokenKind::EOL, "\r\n" ),
+            //  `\t\x0c` and 4 spaces are the same indentation with tab width 4.
+            ( T![ ], "\t\x0c" ), ( TokenKind::Id, "y" ), ( T![ ], " " ),
+            ( T![ '"' ], "\"" ), ( TokenKind::StringFragment, "s" ),
+            ( TokenKind::EOL, "\r\n" ),
+            ( T![ ], "    " ), ( T![ "\"\"\"" ], "\"\"\"" ), ( TokenKind::StringFragment, "\r\n" ), ( T![ "\"\"\"" ], "\"\"\"" ),
+            ( TokenKind::EOL, "\r\n" ),
+            ( TokenKind::Dedent, "" ), ( TokenKind::Id, "z" ), ( T![ ], "\r" ), ( TokenKind::Id, "w" ),
+        ] );
+
+        let errors: Vec< &LexerErrorKind > = lexer.errors().iter().map( | e | &e.kind ).collect();
+        assert_eq!( errors, vec![ &LexerErrorKind::UnterminatedString ] );
+
+        //  With tab width 2 the second line is indented less than the first one.
+        assert_eq!( kinds( Lexer::with_indents( "a:\n\tb:\n    c" ).with_tab_width( 2 ) ), vec![
+            TokenKind::Id, T![ : ],
+            TokenKind::Indent, TokenKind::Id, T![ : ],
+            TokenKind::Indent, TokenKind::Id,
+            TokenKind::Dedent, TokenKind::Dedent,
+        ] );
+    }
+
+    #[ test ]
+    fn format_specs() {
+        let input = "\"${ x:.2 } ${ y:*^10} ${ f( | a:u8 | a ) } ${ { a: 1 } } ${ z:q }\"";
+        let mut lexer = Lexer::new( input );
+        let specs: Vec< &str > = lexer
+            .by_ref()
+            .filter( | t | t.kind == TokenKind::FormatSpec )
+            .map( | t | t.text )
+            .collect();
+        assert_eq!( specs, vec![ ":.2", ":*^10", ":q" ] );
+
+        let errors: Vec< ( usize, usize, &LexerErrorKind ) > = lexer.errors().iter().map( | e | ( e.start, e.end, &e.kind ) ).collect();
+        assert_eq!( errors, vec![
+            ( 61, 63, &LexerErrorKind::InvalidFormatSpec( FormatSpecError::InvalidKind( "q".to_string() ) ) ),
+        ] );
+    }
+
+    #[ test ]
+    fn keywords() {
+        let input = "else return break continue match in true false null import export pub impl trait as elsewhere _ _x => ==>";
+
+        assert_eq!( kinds( Lexer::new( input ) ), vec![
+            T![ else ], T![ return ], T![ break ], T![ continue ], T![ match ], T![ in ],
+            T![ true ], T![ false ], T![ null ],
+            T![ import ], T![ export ], T![ pub ], T![ impl ], T![ trait ], T![ as ],
+            TokenKind::Id,
+            T![ _ ], TokenKind::Id, T![ => ], T![ == ], T![ > ],
+        ] );
+    }
+
+    #[ test ]
+    fn numbers() {
+        let texts: Vec< ( TokenKind, &str ) > = Lexer::new( "1.5 1..2 0xFF_u8 2.5e-3f32 1.foo 1e t.0.1 1..2.5" )
+            .filter( | t | t.kind != T![ ] )
+            .map( | t | ( t.kind, t.text ) )
+            .collect();
+
+        assert_eq!( texts, vec![
+            ( TokenKind::Number, "1.5" ),
+            ( TokenKind::Number, "1" ), ( T![ .. ], ".." ), ( TokenKind::Number, "2" ),
+            ( TokenKind::Number, "0xFF_u8" ),
+            ( TokenKind::Number, "2.5e-3f32" ),
+            ( TokenKind::Number, "1" ), ( T![ . ], "." ), ( TokenKind::Id, "foo" ),
+            ( TokenKind::Number, "1e" ),
+            ( TokenKind::Id, "t" ), ( T![ . ], "." ), ( TokenKind::Number, "0" ), ( T![ . ], "." ), ( TokenKind::Number, "1" ),
+            ( TokenKind::Number, "1" ), ( T![ .. ], ".." ), ( TokenKind::Number, "2.5" ),
+        ] );
+    }
+
+    #[ test ]
+    fn unicode_ids() {
+        let texts: Vec< ( TokenKind, &str ) > = Lexer::new( "let привет_мир = x1ф + _ё // комментарий\n€€ф" )
+            .filter( | t | t.kind != T![ ] )
+            .map( | t | ( t.kind, t.text ) )
+            .collect();
+
+        assert_eq!( texts, vec![
+            ( T![ let ], "let" ),
+            ( TokenKind::Id, "привет_мир" ),
+            ( T![ = ], "=" ),
+            ( TokenKind::Id, "x1ф" ),
+            ( T![ + ], "+" ),
+            ( TokenKind::Id, "_ё" ),
+            ( TokenKind::Comment, "// комментарий" ),
+            ( TokenKind::EOL, "\n" ),
+            ( TokenKind::Unknown, "€€" ),
+            ( TokenKind::Id, "ф" ),
+        ] );
+    }
+
+    #[ test ]
+    fn comments() {
+        let input = "/* a /* b */ c */ x // c\n/// doc\n//! inner\n//// not doc\n/* open";
+        let mut lexer = Lexer::new( input );
+        let texts: Vec< ( TokenKind, &str ) > = lexer.by_ref()
+            .filter( | t | t.kind != T![ ] && t.kind != TokenKind::EOL )
+            .map( | t | ( t.kind, t.text ) )
+            .collect();
+
+        assert_eq!( texts, vec![
+            ( TokenKind::BlockComment, "/* a /* b */ c */" ),
+            ( TokenKind::Id, "x" ),
+            ( TokenKind::Comment, "// c" ),
+            ( TokenKind::DocComment, "/// doc" ),
+            ( TokenKind::InnerDocComment, "//! inner" ),
+            ( TokenKind::Comment, "//// not doc" ),
+            ( TokenKind::BlockComment, "/* open" ),
+        ] );
+
+        let errors: Vec< &LexerErrorKind > = lexer.errors().iter().map( | e | &e.kind ).collect();
+        assert_eq!( errors, vec![ &LexerErrorKind::UnterminatedBlockComment ] );
+    }
+
+    #[ test ]
+    fn multiline_strings() {
+        let input = "\"\"\"\n  a \"b\" ${ f(\n  \"x\"\n) }\n  \"\"\" r#\"c\"\nd\"# \"e\n\"";
+        let texts: Vec< ( TokenKind, &str ) > = Lexer::new( input )
+            .filter( | t | t.kind != T![ ] )
+            .map( | t | ( t.kind, t.text ) )
+            .collect();
+
+        assert_eq!( texts, vec![
+            ( T![ "\"\"\"" ], "\"\"\"" ),
+            ( TokenKind::StringFragment, "\n  a \"b\" " ),
+            ( T![ "${" ], "${" ),
+            ( TokenKind::Id, "f" ),
+            ( T![ '(' ], "(" ),
+            ( TokenKind::EOL, "\n" ),
+            ( T![ '"' ], "\"" ),
+            ( TokenKind::StringFragment, "x" ),
+            ( T![ '"' ], "\"" ),
+            ( TokenKind::EOL, "\n" ),
+            ( T![ ')' ], ")" ),
+            ( T![ '}' ], "}" ),
+            ( TokenKind::StringFragment, "\n  " ),
+            ( T![ "\"\"\"" ], "\"\"\"" ),
+            ( TokenKind::RawString, "r#\"c\"\nd\"#" ),
+            //  Unterminated single line string.
+            ( T![ '"' ], "\"" ),
+            ( TokenKind::StringFragment, "e" ),
+            ( TokenKind::EOL, "\n" ),
+            ( T![ '"' ], "\"" ),
+        ] );
+    }
+
+    #[ test ]
+    fn errors() {
+        let input = "let s = \"a ${ \"b\n€€ x\n\"\"\"c ${ \"d";
+        let mut lexer = Lexer::new( input );
+        let tokens: Vec< Token > = lexer.by_ref().collect();
+        assert!( tokens.iter().any( | t | t.kind == TokenKind::Unknown && t.text == "€€" ) );
+
+        let errors: Vec< ( usize, usize, &LexerErrorKind ) > = lexer.errors().iter().map( | e | ( e.start, e.end, &e.kind ) ).collect();
+        assert_eq!( errors, vec![
+            ( 14, 16, &LexerErrorKind::UnterminatedString ),
+            ( 8, 16, &LexerErrorKind::UnterminatedString ),
+            ( 17, 23, &LexerErrorKind::UnknownChars ),
+            ( 34, 36, &LexerErrorKind::UnterminatedString ),
+            ( 26, 36, &LexerErrorKind::UnterminatedString ),
+        ] );
+    }
+
+    fn check_relex( input: &str, edit: Range< usize >, replacement: &str ) -> TokenEdit {
+        let new_input = format!( "{}{}{}", &input[ .. edit.start ], replacement, &input[ edit.end .. ] );
+
+        let lexed = Lexer::with_indents( input ).lex();
+        let ( relexed, token_edit ) = Lexer::with_indents( &new_input ).relex( &lexed, edit.clone(), replacement );
+        let expected = Lexer::with_indents( &new_input ).lex();
+
+        let spans = | tokens: &[ Token ] | -> Vec< ( TokenKind, usize, usize ) > {
+            tokens.iter().map( | t | ( t.kind, t.start, t.end ) ).collect()
+        };
+        let errors = | errors: &[ LexerError ] | -> Vec< ( LexerErrorKind, usize, usize ) > {
+            errors.iter().map( | e | ( e.kind.clone(), e.start, e.end ) ).collect()
+        };
+        assert_eq!( spans( &relexed.tokens ), spans( &expected.tokens ), "{:?} -> {:?}", input, new_input );
+        assert_eq!( errors( &relexed.errors ), errors( &expected.errors ), "{:?} -> {:?}", input, new_input );
+        assert_eq!( relexed.lines, expected.lines, "{:?} -> {:?}", input, new_input );
+        assert_eq!( lexed.tokens.len() - token_edit.old.len(), relexed.tokens.len() - token_edit.new.len() );
+
+        token_edit
+    }
+
+    #[ test ]
+    fn relex() {
+        let input = "fn f( x ):\n    let s = \"a ${ x + \"b\" } c\"\n    /* d */ g( 1.5 )\n\nstruct Foo {\n    x: number,\n}\n";
+
+        //  Only the edited line is relexed.
+        assert_eq!( check_relex( input, 59 .. 60, "25" ), TokenEdit { old: 34 .. 44, new: 34 .. 44 } );
+
+        let inputs = [
+            input,
+            "a:\n  b:\n    \"\"\"\n    x ${ f(\n  1 ) }\n    \"\"\"\n  c\nd r#\"\n\"#\n",
+            "t.0.1 + 2.5 .. 1.5",
+            "fn f() {\n    if x:\n        g(\n  y ) }\nz:\n    w\n",
+            "a:\n    b:\n      \"\\q\"\n  d\ne /* f\n",
+        ];
+        for input in inputs {
+            for pos in 0 ..= input.len() {
+                for replacement in [ "", "x", "\"", "\"\"\"", "${", "}", "{", "(", ")", ":", "\n", "\n  ", "/*", "//", "r#\"", "1e", ".", "\\q", "€" ] {
+                    for len in [ 0, 1, 3 ] {
+                        if pos + len <= input.len() {
+                            check_relex( input, pos .. pos + len, replacement );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+}
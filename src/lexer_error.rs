@@ -0,0 +1,63 @@
+use std::fmt;
+
+use crate::format_spec::FormatSpecError;
+
+#[ derive( Clone ) ]
+pub struct LexerError {
+    pub start: usize,
+    pub end: usize,
+    pub kind: LexerErrorKind,
+}
+
+impl LexerError {
+
+    pub fn new( start: usize, end: usize, kind: LexerErrorKind ) -> Self {
+        LexerError {
+            start,
+            end,
+            kind,
+        }
+    }
+
+}
+
+impl fmt::Debug for LexerError {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        write!( f , "ERROR: {:?} at {}..{}", self.kind, self.start, self.end )
+    }
+
+}
+
+#[ derive( Clone, PartialEq ) ]
+pub enum LexerErrorKind {
+    InconsistentIndent { expected: usize, found: usize },
+    UnknownDedentLevel( usize ),
+    InvalidEscape( Option< char > ),
+    InvalidUnicodeEscape,
+    UnterminatedBlockComment,
+    UnterminatedRawString,
+    UnterminatedString,
+    UnknownChars,
+    UnbalancedCurly,
+    InvalidFormatSpec( FormatSpecError ),
+}
+
+impl fmt::Debug for LexerErrorKind {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match self {
+            Self::InconsistentIndent { expected, found } => write!( f, "Inconsistent indent: expected {} spaces, found {}", expected, found ),
+            Self::UnknownDedentLevel( width ) => write!( f, "Dedent to unknown indentation level {}", width ),
+            Self::InvalidEscape( Some( c ) ) => write!( f, "Invalid escape sequence \\{}", c ),
+            Self::InvalidEscape( None ) => write!( f, "Invalid escape sequence" ),
+            Self::InvalidUnicodeEscape => write!( f, "Invalid unicode escape sequence" ),
+            Self::UnterminatedBlockComment => write!( f, "Unterminated block comment" ),
+            Self::UnterminatedRawString => write!( f, "Unterminated raw string" ),
+            Self::UnterminatedString => write!( f, "Unterminated string" ),
+            Self::UnknownChars => write!( f, "Unknown characters" ),
+            Self::UnbalancedCurly => write!( f, "Unbalanced `}}` in string interpolation" ),
+            Self::InvalidFormatSpec( error ) => write!( f, "{:?}", error ),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+
+mod token_kind;
+mod token_set;
+mod lexer;
+mod lexer_error;
+mod token_buffer;
+mod unescape;
+mod number;
+mod format_spec;
+mod parser;
+mod parser_error;
+mod syntax_kind;
+mod syntax_node;
+mod grammar;
+mod parser_event;
+mod ast_node;
+pub mod ast;
+mod validation;
+mod const_eval;
+mod methods;
+mod traits;
+mod modules;
+mod analysis;
+
+#[ cfg( test ) ]
+mod bench;
+
+#[ cfg( test ) ]
+mod round_trip;
+//  mod test;
+//  mod types;
+
+pub use syntax_kind::{ SyntaxKind };
+pub use token_kind::{ TokenKind };
+pub use lexer::{ Lexer, Lexed, Token, TokenEdit };
+pub use lexer_error::{ LexerError, LexerErrorKind };
+pub use token_buffer::TokenBuffer;
+pub use syntax_node::{ SyntaxNode, SyntaxElement, InputPos, get_input_pos, DEFAULT_TAB_WIDTH };
+pub use parser_error::{ ParserError, ParserErrorKind };
+pub use parser_event::{ ParserEvent };
+pub use ast_node::AstNode;
+pub use token_set::TokenSet;
+pub use validation::{ ValidationError, ValidationErrorKind, validate };
+pub use const_eval::{ ConstEvaluator, ConstValue, ConstError, ConstErrorKind };
+pub use methods::{ Methods, MethodCall, MethodError, MethodErrorKind, resolve_methods };
+pub use traits::{ TraitError, TraitErrorKind, check_traits };
+pub use modules::{ Module, ModuleError, ModuleErrorKind, Program };
+pub use analysis::{ Analysis, Diagnostic, analyze };
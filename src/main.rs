@@ -1,53 +1,20 @@
-mod token_kind;
-mod token_set;
-mod lexer;
-mod parser;
-mod parser_error;
-mod syntax_kind;
-mod syntax_node;
-mod grammar;
-mod parser_event;
-mod ast_node;
-mod ast;
-//  mod test;
-//  mod types;
+use std::env;
+use std::path::Path;
 
-pub use syntax_kind::{ SyntaxKind };
-pub use token_kind::{ TokenKind };
-pub use lexer::{ Lexer, Token };
-pub use syntax_node::{ SyntaxNode, SyntaxElement };
-use parser::Parser;
-pub use parser_error::{ ParserError, ParserErrorKind };
-use grammar::r_source_file;
-pub use parser_event::{ ParserEvent };
-pub use ast_node::AstNode;
-pub use token_set::TokenSet;
-
-use std::fs;
-
-// . use crate::ast;
+use teya::{ analyze, get_input_pos, DEFAULT_TAB_WIDTH };
 
 //  ---------------------------------------------------------------------------------------------------------------  //
 
+//  `teya path/to/main.teya` checks the module and all modules imported by it.
+//
 fn main() {
-    let content = fs::read_to_string( "./tests/01.teya" ).unwrap();
-    let parser = Parser::new( &content );
-
-    let node = parser.parse( r_source_file );
-
-    let root = ast::Root::cast( &node ).unwrap();
-
-    root.walk( | x: &ast::Fn | {
-        println!( "{:?}", x.name.as_ref().unwrap().id().unwrap() );
-    } );
+    let entry = env::args().nth( 1 ).unwrap_or_else( || "./tests/01.teya".to_string() );
+    let analysis = analyze( Path::new( &entry ) );
 
-    let source_file = root.source_file.unwrap();
-    let item0 = source_file.items.get( 0 ).unwrap();
-    if let ast::Item::Fn( fn0 ) = item0 {
-        let name = fn0.name.as_ref().unwrap();
-        let id = name.id().unwrap();
+    for diagnostic in &analysis.diagnostics {
+        let source = analysis.program.module( &diagnostic.path ).map_or( "", | module | module.source.as_str() );
+        let pos = get_input_pos( source, diagnostic.start, DEFAULT_TAB_WIDTH );
 
-        // println!( "{:?}", node );
-        println!( "{}", id.text );
+        println!( "{}:{}:{}: {}", diagnostic.path.display(), pos.line + 1, pos.char_column + 1, diagnostic.message );
     }
 }
@@ -0,0 +1,504 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{ SyntaxNode, SyntaxKind, TokenKind };
+
+//  Resolution of method calls `a.foo()` to `StructMethod` nodes, or `TraitMethod` for default methods
+//  and receivers of trait types:
+//
+//      struct Point {
+//          x: number,
+//          fn new() -> Point { Point { x: 0 } }
+//          fn len( &self ) -> number { self.x }
+//      }
+//
+//      let p = Point.new()     //  static method, called on the struct
+//      p.len()                 //  instance method, called on a value
+//
+//  There is no type inference yet, the receiver type is known for `self`, struct names, struct literals,
+//  annotated parameters and `let`s, and results of resolved methods. Calls on other receivers are left
+//  unresolved without errors.
+//
+//  Methods of `impl Trait for Type` blocks and default methods of the trait belong to `Type`, conflicts
+//  are reported by `traits`.
+//
+
+#[ derive( Clone ) ]
+pub struct MethodError {
+    pub start: usize,
+    pub end: usize,
+    pub kind: MethodErrorKind,
+}
+
+impl fmt::Debug for MethodError {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        write!( f , "ERROR: {:?} at {}..{}", self.kind, self.start, self.end )
+    }
+
+}
+
+#[ derive( Clone, PartialEq ) ]
+pub enum MethodErrorKind {
+    UnknownMethod( String, String ),
+    StaticMethodOnInstance( String ),
+    InstanceMethodOnStruct( String ),
+}
+
+impl fmt::Debug for MethodErrorKind {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match self {
+            Self::UnknownMethod( ty, name ) => write!( f, "No method {} in {}", name, ty ),
+            Self::StaticMethodOnInstance( name ) => write!( f, "Static method {} called on an instance", name ),
+            Self::InstanceMethodOnStruct( name ) => write!( f, "Method {} requires an instance", name ),
+        }
+    }
+
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+pub struct MethodCall< 'a > {
+    //  `InlineMethodCall`
+    pub call: &'a SyntaxNode< 'a >,
+    //  `StructMethod` or `TraitMethod`
+    pub method: &'a SyntaxNode< 'a >,
+    //  Struct, enum or trait.
+    pub type_name: &'a str,
+}
+
+pub struct Methods< 'a > {
+    pub calls: Vec< MethodCall< 'a > >,
+    pub errors: Vec< MethodError >,
+}
+
+impl < 'a > Methods< 'a > {
+
+    //  Method called by the `InlineMethodCall` node.
+    pub fn method( &self, call: &SyntaxNode ) -> Option< &'a SyntaxNode< 'a > > {
+        self.calls
+            .iter()
+            .find( | x | std::ptr::eq( x.call, call ) )
+            .map( | x | x.method )
+    }
+
+}
+
+pub fn resolve_methods< 'a >( root: &'a SyntaxNode< 'a > ) -> Methods< 'a > {
+    let mut types = HashMap::new();
+    let mut impls = Vec::new();
+    collect_types( root, &mut types, &mut impls );
+    add_impl_methods( &impls, &mut types );
+
+    let mut resolver = Resolver {
+        types,
+        current_type: None,
+        scopes: Vec::new(),
+        calls: Vec::new(),
+        errors: Vec::new(),
+    };
+    resolver.node( root );
+
+    Methods {
+        calls: resolver.calls,
+        errors: resolver.errors,
+    }
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+#[ derive( Clone, Copy ) ]
+enum Receiver< 'a > {
+    //  `Point` of `Point.new()`.
+    Type( &'a str ),
+    //  Value of the type.
+    Instance( &'a str ),
+}
+
+//  Methods of a type by name.
+type TypeMethods< 'a > = HashMap< &'a str, &'a SyntaxNode< 'a > >;
+
+struct Resolver< 'a > {
+    types: HashMap< &'a str, TypeMethods< 'a > >,
+    //  Type of `self`.
+    current_type: Option< &'a str >,
+    //  Struct types of local variables, `None` if unknown. Innermost scope last.
+    scopes: Vec< HashMap< &'a str, Option< &'a str > > >,
+    calls: Vec< MethodCall< 'a > >,
+    errors: Vec< MethodError >,
+}
+
+impl < 'a > Resolver< 'a > {
+
+    fn node( &mut self, node: &'a SyntaxNode< 'a > ) {
+        match node.kind {
+            SyntaxKind::Struct | SyntaxKind::Trait | SyntaxKind::Impl => {
+                let name = if node.kind == SyntaxKind::Impl {
+                    node.find_node_by_index( SyntaxKind::Type, 0 ).and_then( type_name )
+                } else {
+                    node.name()
+                };
+                let current = std::mem::replace( &mut self.current_type, name );
+
+                self.children( node );
+
+                self.current_type = current;
+            }
+
+            SyntaxKind::StructMethod | SyntaxKind::TraitMethod | SyntaxKind::Fn | SyntaxKind::Closure => {
+                //  Locals of enclosing functions are visible in closures only.
+                let scopes = if node.kind == SyntaxKind::Closure {
+                    None
+                } else {
+                    Some( std::mem::take( &mut self.scopes ) )
+                };
+
+                self.scopes.push( HashMap::new() );
+                if let Some( params ) = node.find_node_by_index( SyntaxKind::FnParams, 0 ) {
+                    if params.find_node_by_index( SyntaxKind::SelfParam, 0 ).is_some() {
+                        self.bind( "self", self.current_type );
+                    }
+                    for param in params.find_nodes( SyntaxKind::FnParam ) {
+                        self.bind_param( param );
+                    }
+                }
+                if let Some( params ) = node.find_node_by_index( SyntaxKind::ClosureParams, 0 ) {
+                    for param in params.find_nodes( SyntaxKind::ClosureParam ) {
+                        self.bind_param( param );
+                    }
+                }
+
+                self.children( node );
+
+                self.scopes.pop();
+                if let Some( scopes ) = scopes {
+                    self.scopes = scopes;
+                }
+            }
+
+            SyntaxKind::Block | SyntaxKind::For | SyntaxKind::MatchArm => {
+                self.scopes.push( HashMap::new() );
+                self.children( node );
+                self.scopes.pop();
+            }
+
+            SyntaxKind::Let => {
+                self.children( node );
+
+                if let Some( name ) = node.name() {
+                    let ty = match node.find_node_by_index( SyntaxKind::Type, 0 ) {
+                        Some( ty ) => type_name( ty ),
+                        None => node.nodes().last().and_then( | value | match self.receiver( value ) {
+                            Some( Receiver::Instance( ty ) ) => Some( ty ),
+                            _ => None,
+                        } ),
+                    };
+                    self.bind( name, ty );
+                }
+            }
+
+            //  Shadows outer variables with the same name.
+            SyntaxKind::BindingPattern => {
+                if let Some( name ) = node.name() {
+                    self.bind( name, None );
+                }
+            }
+
+            SyntaxKind::InlineMethodCall => {
+                self.children( node );
+
+                match self.lookup( node ) {
+                    Some( Ok( ( type_name, method ) ) ) => self.calls.push( MethodCall {
+                        call: node,
+                        method,
+                        type_name,
+                    } ),
+                    Some( Err( kind ) ) => {
+                        if let Some( token ) = node.find_token( TokenKind::Id ) {
+                            self.errors.push( MethodError {
+                                start: token.start,
+                                end: token.end,
+                                kind,
+                            } );
+                        }
+                    }
+                    None => {},
+                }
+            }
+
+            _ => self.children( node ),
+        }
+    }
+
+    fn children( &mut self, node: &'a SyntaxNode< 'a > ) {
+        for child in node.nodes() {
+            self.node( child );
+        }
+    }
+
+    fn bind( &mut self, name: &'a str, ty: Option< &'a str > ) {
+        if let Some( scope ) = self.scopes.last_mut() {
+            scope.insert( name, ty );
+        }
+    }
+
+    //  `FnParam` or `ClosureParam`, `name: Type`.
+    fn bind_param( &mut self, param: &'a SyntaxNode< 'a > ) {
+        if let Some( name ) = param.find_token( TokenKind::Id ) {
+            let ty = param.find_node_by_index( SyntaxKind::Type, 0 ).and_then( type_name );
+            self.bind( name.text, ty );
+        }
+    }
+
+    fn local( &self, name: &str ) -> Option< Option< &'a str > > {
+        self.scopes.iter().rev().find_map( | scope | scope.get( name ).copied() )
+    }
+
+    //  Method called by `InlineMethodCall`, `None` if the receiver type is unknown.
+    fn lookup( &self, call: &'a SyntaxNode< 'a > ) -> Option< Result< ( &'a str, &'a SyntaxNode< 'a > ), MethodErrorKind > > {
+        let receiver = self.receiver( call.nodes().next()? )?;
+        let name = call.find_token( TokenKind::Id )?.text;
+
+        let ( Receiver::Type( type_name ) | Receiver::Instance( type_name ) ) = receiver;
+        let Some( method ) = self.types.get( type_name )?.get( name ).copied() else {
+            return Some( Err( MethodErrorKind::UnknownMethod( type_name.to_string(), name.to_string() ) ) );
+        };
+
+        let is_static = method
+            .find_node_by_index( SyntaxKind::FnParams, 0 )
+            .and_then( | params | params.find_node_by_index( SyntaxKind::SelfParam, 0 ) )
+            .is_none();
+
+        match receiver {
+            Receiver::Instance( _ ) if is_static => Some( Err( MethodErrorKind::StaticMethodOnInstance( name.to_string() ) ) ),
+            Receiver::Type( _ ) if !is_static => Some( Err( MethodErrorKind::InstanceMethodOnStruct( name.to_string() ) ) ),
+            _ => Some( Ok( ( type_name, method ) ) ),
+        }
+    }
+
+    fn receiver( &self, expr: &'a SyntaxNode< 'a > ) -> Option< Receiver< 'a > > {
+        match expr.kind {
+            SyntaxKind::InlineVar => {
+                let name = expr.find_token( TokenKind::Id )?.text;
+
+                match self.local( name ) {
+                    Some( ty ) => ty.map( Receiver::Instance ),
+                    None if self.types.contains_key( name ) => Some( Receiver::Type( name ) ),
+                    None => None,
+                }
+            }
+
+            SyntaxKind::InlineStruct => match self.receiver( expr.nodes().next()? )? {
+                Receiver::Type( name ) => Some( Receiver::Instance( name ) ),
+                Receiver::Instance( _ ) => None,
+            },
+
+            SyntaxKind::Expr | SyntaxKind::InlineSubexpr => self.receiver( expr.nodes().next()? ),
+
+            SyntaxKind::InlineMethodCall => {
+                let ( _, method ) = self.lookup( expr )?.ok()?;
+
+                method
+                    .find_node_by_index( SyntaxKind::FnReturnType, 0 )
+                    .and_then( | ty | ty.find_node_by_index( SyntaxKind::Type, 0 ) )
+                    .and_then( type_name )
+                    .filter( | name | self.types.contains_key( name ) )
+                    .map( Receiver::Instance )
+            }
+
+            _ => None,
+        }
+    }
+
+}
+
+//  `Point` of `Type( TypeRef( Point ) )`.
+//
+fn type_name< 'a >( ty: &'a SyntaxNode< 'a > ) -> Option< &'a str > {
+    ty.find_node_by_index( SyntaxKind::TypeRef, 0 )?
+        .find_token( TokenKind::Id )
+        .map( | token | token.text )
+}
+
+//  Structs, enums and traits with their own methods.
+//
+fn collect_types< 'a >( node: &'a SyntaxNode< 'a >, types: &mut HashMap< &'a str, TypeMethods< 'a > >, impls: &mut Vec< &'a SyntaxNode< 'a > > ) {
+    for child in node.nodes() {
+        //  Kinds of the items node and of methods in it.
+        let kinds = match child.kind {
+            SyntaxKind::Struct => Some( ( SyntaxKind::StructItems, SyntaxKind::StructMethod ) ),
+            SyntaxKind::Trait => Some( ( SyntaxKind::TraitItems, SyntaxKind::TraitMethod ) ),
+            SyntaxKind::Enum => None,
+            SyntaxKind::Impl => {
+                impls.push( child );
+                continue;
+            }
+            _ => {
+                collect_types( child, types, impls );
+                continue;
+            }
+        };
+
+        let Some( name ) = child.name() else {
+            continue;
+        };
+
+        let mut methods = HashMap::new();
+        if let Some( ( items, method_kind ) ) = kinds {
+            if let Some( items ) = child.find_node_by_index( items, 0 ) {
+                add_methods( items.find_nodes( method_kind ), &mut methods );
+            }
+        }
+        types.entry( name ).or_insert( methods );
+    }
+}
+
+//  Methods of impls and default methods of their traits.
+//
+fn add_impl_methods< 'a >( impls: &[ &'a SyntaxNode< 'a > ], types: &mut HashMap< &'a str, TypeMethods< 'a > > ) {
+    for node in impls {
+        let Some( target ) = node.find_node_by_index( SyntaxKind::Type, 0 ).and_then( type_name ) else {
+            continue;
+        };
+        let defaults: Vec< &SyntaxNode > = node
+            .find_node_by_index( SyntaxKind::TypeRef, 0 )
+            .and_then( | trait_ref | trait_ref.find_token( TokenKind::Id ) )
+            .and_then( | name | types.get( name.text ) )
+            .map( | methods | methods.values().copied().filter( | method | method.kind == SyntaxKind::TraitMethod ).collect() )
+            .unwrap_or_default();
+
+        let methods = types.entry( target ).or_default();
+        if let Some( items ) = node.find_node_by_index( SyntaxKind::ImplItems, 0 ) {
+            add_methods( items.find_nodes( SyntaxKind::StructMethod ), methods );
+        }
+        add_methods( defaults.into_iter().filter( | method | method.find_node_by_index( SyntaxKind::Block, 0 ).is_some() ).collect(), methods );
+    }
+}
+
+fn add_methods< 'a >( nodes: Vec< &'a SyntaxNode< 'a > >, methods: &mut TypeMethods< 'a > ) {
+    for method in nodes {
+        if let Some( name ) = method.name() {
+            methods.entry( name ).or_insert( method );
+        }
+    }
+}
+
+#[ cfg( test ) ]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::grammar::r_source_file;
+
+    const POINT: &str = "\
+struct Point {
+    x: number,
+    fn new( x: number ) -> Point { Point { x } }
+    fn len( &self ) -> number { self.x }
+    fn moved( self, dx: number ) -> Point {
+        Point.new( self.x + dx ).moved( 0 )
+    }
+}
+";
+
+    //  Resolved calls with method names and errors with their text.
+    type Resolved = ( Vec< ( String, String ) >, Vec< ( MethodErrorKind, String ) > );
+
+    fn resolved( input: &str ) -> Resolved {
+        let input = format!( "{}{}", POINT, input );
+        let node = Parser::new( &input ).parse( r_source_file );
+        let methods = resolve_methods( &node );
+
+        let calls = methods.calls
+            .iter()
+            .map( | x | ( x.call.text().trim().to_string(), x.method.name().unwrap().to_string() ) )
+            .collect();
+        let errors = methods.errors
+            .into_iter()
+            .map( | e | ( e.kind, input[ e.start .. e.end ].to_string() ) )
+            .collect();
+
+        ( calls, errors )
+    }
+
+    #[ test ]
+    fn calls() {
+        let ( calls, errors ) = resolved( "\
+fn f( p: Point, n: number ) {
+    let q = Point.new( 1 )
+    let r: Point = q
+    p.len()
+    q.moved( 1 ).len()
+    ( r ).len()
+    Point { x: 1 }.len()
+    n.len()
+    g().len()
+}
+" );
+
+        assert_eq!( calls, vec![
+            ( "Point.new( self.x + dx )".to_string(), "new".to_string() ),
+            ( "Point.new( self.x + dx ).moved( 0 )".to_string(), "moved".to_string() ),
+            ( "Point.new( 1 )".to_string(), "new".to_string() ),
+            ( "p.len()".to_string(), "len".to_string() ),
+            ( "q.moved( 1 )".to_string(), "moved".to_string() ),
+            ( "q.moved( 1 ).len()".to_string(), "len".to_string() ),
+            ( "( r ).len()".to_string(), "len".to_string() ),
+            ( "Point { x: 1 }.len()".to_string(), "len".to_string() ),
+        ] );
+        assert!( errors.is_empty() );
+    }
+
+    #[ test ]
+    fn errors() {
+        let ( _, errors ) = resolved( "\
+fn f( p: Point ) {
+    p.area()
+    p.new( 1 )
+    Point.len()
+    let p = 1
+    p.area()
+    for Point in xs { Point.area() }
+}
+" );
+
+        assert_eq!( errors, vec![
+            ( MethodErrorKind::UnknownMethod( "Point".to_string(), "area".to_string() ), "area".to_string() ),
+            ( MethodErrorKind::StaticMethodOnInstance( "new".to_string() ), "new".to_string() ),
+            ( MethodErrorKind::InstanceMethodOnStruct( "len".to_string() ), "len".to_string() ),
+        ] );
+    }
+
+    #[ test ]
+    fn trait_methods() {
+        let ( calls, errors ) = resolved( "\
+trait Shape {
+    fn area( &self ) -> number;
+    fn twice( &self ) -> number { self.area() * 2 }
+}
+impl Shape for Point {
+    fn area( &self ) -> number { self.len() }
+}
+fn f( p: Point, s: Shape ) {
+    p.area()
+    p.twice()
+    s.twice()
+    Point.area()
+}
+" );
+
+        assert_eq!( calls, vec![
+            ( "Point.new( self.x + dx )".to_string(), "new".to_string() ),
+            ( "Point.new( self.x + dx ).moved( 0 )".to_string(), "moved".to_string() ),
+            ( "self.area()".to_string(), "area".to_string() ),
+            ( "self.len()".to_string(), "len".to_string() ),
+            ( "p.area()".to_string(), "area".to_string() ),
+            ( "p.twice()".to_string(), "twice".to_string() ),
+            ( "s.twice()".to_string(), "twice".to_string() ),
+        ] );
+        assert_eq!( errors, vec![
+            ( MethodErrorKind::InstanceMethodOnStruct( "area".to_string() ), "area".to_string() ),
+        ] );
+    }
+
+}
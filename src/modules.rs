@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{ Component, Path, PathBuf };
+
+use crate::{ SyntaxNode, SyntaxKind, AstNode, ParserError, LexerError };
+use crate::ast;
+use crate::parser::Parser;
+use crate::grammar::r_source_file;
+
+//  Modules are source files, items marked `pub` or `export` can be imported by other modules:
+//
+//      //  main.teya
+//      import { add, Point as P } from "./math"
+//
+//      //  math.teya
+//      pub fn add( x: number, y: number ) -> number { x + y }
+//      export struct Point { x: number }
+//
+//  Import paths are relative to the importing file, `.teya` is added if there's no extension.
+//  Modules can't import each other in a cycle.
+//
+
+pub const EXTENSION: &str = "teya";
+
+pub struct Module {
+    //  Canonical path.
+    pub path: PathBuf,
+    pub source: String,
+    //  Errors of the source, found when the module is loaded.
+    pub errors: Vec< ParserError >,
+    pub lexer_errors: Vec< LexerError >,
+    //  Paths of the imported modules, in the order of imports.
+    pub imports: Vec< PathBuf >,
+}
+
+impl Module {
+
+    //  The tree borrows the source, so it is parsed again by each pass that needs it.
+    //
+    pub fn parse( &self ) -> SyntaxNode< '_ > {
+        Parser::new( &self.source ).parse( r_source_file )
+    }
+
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+#[ derive( Clone ) ]
+pub struct ModuleError {
+    //  Module with the error.
+    pub path: PathBuf,
+    pub start: usize,
+    pub end: usize,
+    pub kind: ModuleErrorKind,
+}
+
+impl fmt::Debug for ModuleError {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        write!( f , "ERROR: {:?} at {}..{} in {}", self.kind, self.start, self.end, self.path.display() )
+    }
+
+}
+
+#[ derive( Clone, PartialEq ) ]
+pub enum ModuleErrorKind {
+    NotFound( PathBuf ),
+    InvalidPath,
+    //  Modules of the cycle, the first one is repeated at the end.
+    Cycle( Vec< PathBuf > ),
+    UnknownName( String ),
+    NotExported( String ),
+    DuplicateName( String ),
+}
+
+impl fmt::Debug for ModuleErrorKind {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match self {
+            Self::NotFound( path ) => write!( f, "Module {} not found", path.display() ),
+            Self::InvalidPath => write!( f, "Import path must be a string without interpolations" ),
+            Self::Cycle( paths ) => {
+                let paths: Vec< String > = paths.iter().map( | path | path.display().to_string() ).collect();
+                write!( f, "Import cycle {}", paths.join( " -> " ) )
+            }
+            Self::UnknownName( name ) => write!( f, "Module has no item {}", name ),
+            Self::NotExported( name ) => write!( f, "Item {} is not exported", name ),
+            Self::DuplicateName( name ) => write!( f, "Name {} is already defined in the module", name ),
+        }
+    }
+
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+//  Modules reachable from the entry module by imports.
+//
+pub struct Program {
+    //  Imported modules go before the importing ones, the entry module is the last.
+    pub modules: Vec< Module >,
+    pub errors: Vec< ModuleError >,
+}
+
+impl Program {
+
+    pub fn load( entry: &Path ) -> Self {
+        let mut loader = Loader {
+            modules: Vec::new(),
+            items: HashMap::new(),
+            stack: Vec::new(),
+            errors: Vec::new(),
+        };
+
+        match fs::canonicalize( entry ) {
+            Ok( path ) => loader.load( path ),
+            Err( _ ) => loader.errors.push( ModuleError {
+                path: entry.to_path_buf(),
+                start: 0,
+                end: 0,
+                kind: ModuleErrorKind::NotFound( entry.to_path_buf() ),
+            } ),
+        }
+
+        Program {
+            modules: loader.modules,
+            errors: loader.errors,
+        }
+    }
+
+    pub fn module( &self, path: &Path ) -> Option< &Module > {
+        self.modules.iter().find( | module | module.path == path )
+    }
+
+}
+
+struct Loader {
+    modules: Vec< Module >,
+    //  Names of items with their visibility by module, known before the imports of the module are loaded.
+    items: HashMap< PathBuf, Vec< ( String, bool ) > >,
+    //  Modules being loaded, the importing one is before the imported one.
+    stack: Vec< PathBuf >,
+    errors: Vec< ModuleError >,
+}
+
+impl Loader {
+
+    fn load( &mut self, path: PathBuf ) {
+        let source = match fs::read_to_string( &path ) {
+            Ok( source ) => source,
+            Err( _ ) => {
+                self.errors.push( ModuleError {
+                    path: path.clone(),
+                    start: 0,
+                    end: 0,
+                    kind: ModuleErrorKind::NotFound( path ),
+                } );
+                return;
+            }
+        };
+
+        self.stack.push( path.clone() );
+
+        let ( node, errors, lexer_errors ) = Parser::new( &source ).parse_with_errors( r_source_file );
+        self.items.insert( path.clone(), items( &node ) );
+
+        let mut imports = Vec::new();
+        for import in imports_of( &node ) {
+            let error = | kind | {
+                let ( start, end ) = import.path.as_ref().and_then( | x | x.syntax().range() ).unwrap_or( ( 0, 0 ) );
+                ModuleError {
+                    path: path.clone(),
+                    start,
+                    end,
+                    kind,
+                }
+            };
+
+            let Some( import_path ) = resolve_path( &path, &import ) else {
+                self.errors.push( error( ModuleErrorKind::InvalidPath ) );
+                continue;
+            };
+
+            let Ok( import_path ) = fs::canonicalize( &import_path ) else {
+                self.errors.push( error( ModuleErrorKind::NotFound( import_path ) ) );
+                continue;
+            };
+
+            if let Some( i ) = self.stack.iter().position( | x | *x == import_path ) {
+                let mut cycle = self.stack[ i .. ].to_vec();
+                cycle.push( import_path.clone() );
+                self.errors.push( error( ModuleErrorKind::Cycle( cycle ) ) );
+
+            } else if !self.modules.iter().any( | module | module.path == import_path ) {
+                self.load( import_path.clone() );
+            }
+
+            imports.push( import_path );
+        }
+
+        self.check_imports( &path, &node );
+        self.stack.pop();
+
+        self.modules.push( Module {
+            path,
+            source,
+            errors,
+            lexer_errors,
+            imports,
+        } );
+    }
+
+    //  Imported names exist and are exported, they don't clash with items of the module.
+    //  Items of the imported modules are known, even of the ones in a cycle.
+    //
+    fn check_imports( &mut self, path: &Path, node: &SyntaxNode ) {
+        let mut names: Vec< String > = self.items[ path ].iter().map( | ( name, _ ) | name.clone() ).collect();
+
+        for import in imports_of( node ) {
+            //  Invalid paths and missing modules are already reported.
+            let Some( target ) = import_path( path, &import ) else {
+                continue;
+            };
+
+            for name in import.names.iter() {
+                let error = | token: &crate::Token, kind | ModuleError {
+                    path: path.to_path_buf(),
+                    start: token.start,
+                    end: token.end,
+                    kind,
+                };
+
+                if let Some( token ) = name.name() {
+                    match self.items.get( &target ).map( | items | items.iter().find( | ( x, _ ) | x == token.text ) ) {
+                        Some( None ) => self.errors.push( error( token, ModuleErrorKind::UnknownName( token.text.to_string() ) ) ),
+                        Some( Some( ( _, false ) ) ) => self.errors.push( error( token, ModuleErrorKind::NotExported( token.text.to_string() ) ) ),
+                        _ => {},
+                    }
+                }
+
+                if let Some( token ) = name.local_name() {
+                    if names.iter().any( | x | x == token.text ) {
+                        self.errors.push( error( token, ModuleErrorKind::DuplicateName( token.text.to_string() ) ) );
+                    } else {
+                        names.push( token.text.to_string() );
+                    }
+                }
+            }
+        }
+    }
+
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+//  Path of the imported module relative to the importing module `from`, not checked to exist.
+//
+fn resolve_path( from: &Path, import: &ast::Import ) -> Option< PathBuf > {
+    let value = import.path_value()?;
+    if value.is_empty() {
+        return None;
+    }
+
+    //  `.` and `..` are removed, so missing modules are reported with a clean path.
+    let mut path = PathBuf::new();
+    for component in from.parent().unwrap_or( Path::new( "" ) ).join( value ).components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir if matches!( path.components().next_back(), Some( Component::Normal( _ ) ) ) => {
+                path.pop();
+            }
+            component => path.push( component ),
+        }
+    }
+
+    if path.extension().is_none() {
+        path.set_extension( EXTENSION );
+    }
+
+    Some( path )
+}
+
+//  Canonical path of the imported module, `None` if it doesn't exist.
+//
+pub fn import_path( from: &Path, import: &ast::Import ) -> Option< PathBuf > {
+    fs::canonicalize( resolve_path( from, import )? ).ok()
+}
+
+fn imports_of< 'a >( node: &'a SyntaxNode< 'a > ) -> Vec< ast::Import< 'a > > {
+    source_file_items( node )
+        .filter( | item | item.kind == SyntaxKind::Import )
+        .filter_map( ast::Import::cast )
+        .collect()
+}
+
+//  Names of items with their visibility.
+//
+fn items( node: &SyntaxNode ) -> Vec< ( String, bool ) > {
+    source_file_items( node )
+        .filter_map( | item | {
+            let name = item.name()?;
+            let exported = item.find_node_by_index( SyntaxKind::Visibility, 0 ).is_some();
+
+            Some( ( name.to_string(), exported ) )
+        } )
+        .collect()
+}
+
+pub fn source_file_items< 'a >( node: &'a SyntaxNode< 'a > ) -> impl Iterator< Item = &'a SyntaxNode< 'a > > {
+    node.find_node_by_index( SyntaxKind::SourceFile, 0 )
+        .into_iter()
+        .flat_map( | source_file | source_file.nodes() )
+}
+
+#[ cfg( test ) ]
+mod tests {
+    use super::*;
+
+    fn fixture( name: &str ) -> PathBuf {
+        Path::new( env!( "CARGO_MANIFEST_DIR" ) ).join( "tests/modules" ).join( name )
+    }
+
+    //  Paths relative to the fixture dir.
+    fn relative( dir: &Path, path: &Path ) -> String {
+        path.strip_prefix( fs::canonicalize( dir ).unwrap() ).unwrap().display().to_string()
+    }
+
+    fn errors( program: &Program, dir: &Path ) -> Vec< ( String, ModuleErrorKind, String ) > {
+        program.errors
+            .iter()
+            .map( | e | {
+                let source = program.module( &e.path ).map_or( "", | module | &module.source );
+                ( relative( dir, &e.path ), e.kind.clone(), source[ e.start .. e.end ].to_string() )
+            } )
+            .collect()
+    }
+
+    #[ test ]
+    fn load() {
+        let dir = fixture( "basic" );
+        let program = Program::load( &dir.join( "main.teya" ) );
+
+        let paths: Vec< String > = program.modules.iter().map( | module | relative( &dir, &module.path ) ).collect();
+        assert_eq!( paths, vec![ "math.teya", "shapes/point.teya", "main.teya" ] );
+
+        let main = program.modules.last().unwrap();
+        let imports: Vec< String > = main.imports.iter().map( | path | relative( &dir, path ) ).collect();
+        assert_eq!( imports, vec![ "math.teya", "shapes/point.teya" ] );
+
+        //  `shapes/point` imports `../math` too.
+        let point = &program.modules[ 1 ];
+        let imports: Vec< String > = point.imports.iter().map( | path | relative( &dir, path ) ).collect();
+        assert_eq!( imports, vec![ "math.teya" ] );
+
+        assert!( program.errors.is_empty(), "{:?}", program.errors );
+    }
+
+    #[ test ]
+    fn cycles() {
+        let dir = fixture( "cycle" );
+        let program = Program::load( &dir.join( "a.teya" ) );
+
+        let paths: Vec< String > = program.modules.iter().map( | module | relative( &dir, &module.path ) ).collect();
+        assert_eq!( paths, vec![ "c.teya", "b.teya", "a.teya" ] );
+
+        let errors: Vec< ( String, String ) > = program.errors
+            .iter()
+            .map( | e | match &e.kind {
+                ModuleErrorKind::Cycle( paths ) => (
+                    relative( &dir, &e.path ),
+                    paths.iter().map( | path | relative( &dir, path ) ).collect::< Vec< String > >().join( " -> " ),
+                ),
+                kind => panic!( "{:?}", kind ),
+            } )
+            .collect();
+        assert_eq!( errors, vec![
+            ( "c.teya".to_string(), "a.teya -> b.teya -> c.teya -> a.teya".to_string() ),
+            ( "b.teya".to_string(), "b.teya -> b.teya".to_string() ),
+        ] );
+    }
+
+    #[ test ]
+    fn import_errors() {
+        let dir = fixture( "errors" );
+        let program = Program::load( &dir.join( "main.teya" ) );
+
+        assert_eq!( errors( &program, &dir ), vec![
+            ( "main.teya".to_string(), ModuleErrorKind::NotFound( fs::canonicalize( &dir ).unwrap().join( "missing.teya" ) ), "\"./missing\"".to_string() ),
+            ( "main.teya".to_string(), ModuleErrorKind::InvalidPath, "\"\"".to_string() ),
+            ( "main.teya".to_string(), ModuleErrorKind::UnknownName( "sub".to_string() ), "sub".to_string() ),
+            ( "main.teya".to_string(), ModuleErrorKind::NotExported( "helper".to_string() ), "helper".to_string() ),
+            ( "main.teya".to_string(), ModuleErrorKind::DuplicateName( "add".to_string() ), "add".to_string() ),
+            ( "main.teya".to_string(), ModuleErrorKind::DuplicateName( "Point".to_string() ), "Point".to_string() ),
+        ] );
+
+        //  Without `./` of the import path.
+        assert_eq!(
+            format!( "{:?}", program.errors[ 0 ].kind ),
+            format!( "Module {} not found", fs::canonicalize( &dir ).unwrap().join( "missing.teya" ).display() ),
+        );
+
+        let program = Program::load( &dir.join( "nothing.teya" ) );
+        assert!( program.modules.is_empty() );
+        assert!( matches!( program.errors[ 0 ].kind, ModuleErrorKind::NotFound( _ ) ) );
+    }
+
+}
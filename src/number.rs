@@ -0,0 +1,263 @@
+use std::fmt;
+
+//  Numeric literals:
+//
+//      42  1_000_000  0xFF  0o777  0b1010_1010
+//      1.5  1e10  2.5E-3
+//      255u8  1i64  1f32  0.5f64
+//
+
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+pub enum NumberValue {
+    Int( u64 ),
+    Float( f64 ),
+}
+
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+pub enum NumberSuffix {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+}
+
+impl NumberSuffix {
+
+    fn from_str( s: &str ) -> Option< Self > {
+        match s {
+            "i8" => Some( Self::I8 ),
+            "i16" => Some( Self::I16 ),
+            "i32" => Some( Self::I32 ),
+            "i64" => Some( Self::I64 ),
+            "u8" => Some( Self::U8 ),
+            "u16" => Some( Self::U16 ),
+            "u32" => Some( Self::U32 ),
+            "u64" => Some( Self::U64 ),
+            "f32" => Some( Self::F32 ),
+            "f64" => Some( Self::F64 ),
+            _ => None,
+        }
+    }
+
+    pub fn is_float( self ) -> bool {
+        matches!( self, Self::F32 | Self::F64 )
+    }
+
+    fn max_int( self ) -> u64 {
+        match self {
+            Self::I8 => i8::MAX as u64,
+            Self::I16 => i16::MAX as u64,
+            Self::I32 => i32::MAX as u64,
+            Self::I64 => i64::MAX as u64,
+            Self::U8 => u8::MAX as u64,
+            Self::U16 => u16::MAX as u64,
+            Self::U32 => u32::MAX as u64,
+            Self::U64 | Self::F32 | Self::F64 => u64::MAX,
+        }
+    }
+
+    //  Signed types fit one more negative value: `-128i8`.
+    fn max_negative_int( self ) -> u64 {
+        match self {
+            Self::I8 | Self::I16 | Self::I32 | Self::I64 => self.max_int() + 1,
+            _ => self.max_int(),
+        }
+    }
+
+}
+
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+pub struct Number {
+    pub value: NumberValue,
+    pub suffix: Option< NumberSuffix >,
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+#[ derive( Clone, PartialEq ) ]
+pub enum NumberError {
+    MissingDigits,
+    InvalidDigit( char ),
+    InvalidSuffix( String ),
+    Overflow( Option< NumberSuffix > ),
+}
+
+impl fmt::Debug for NumberError {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match self {
+            Self::MissingDigits => write!( f, "Number has no digits" ),
+            Self::InvalidDigit( c ) => write!( f, "Invalid digit {:?} in number", c ),
+            Self::InvalidSuffix( suffix ) => write!( f, "Invalid number suffix {:?}", suffix ),
+            Self::Overflow( Some( suffix ) ) => write!( f, "Number does not fit into {:?}", suffix ),
+            Self::Overflow( None ) => write!( f, "Number is too large" ),
+        }
+    }
+
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+//  `negative` if the literal is the operand of unary `-`, the value is still the absolute one.
+//
+pub fn parse_number( text: &str, negative: bool ) -> Result< Number, NumberError > {
+    let ( radix, body ) = match text.get( 0 .. 2 ) {
+        Some( "0x" ) | Some( "0X" ) => ( 16, &text[ 2 .. ] ),
+        Some( "0o" ) | Some( "0O" ) => ( 8, &text[ 2 .. ] ),
+        Some( "0b" ) | Some( "0B" ) => ( 2, &text[ 2 .. ] ),
+        _ => ( 10, text ),
+    };
+
+    let ( digits, suffix, is_float ) = if radix == 10 {
+        split_decimal( body )
+
+    } else {
+        //  `f` is a hex digit, so hex numbers can only have integer suffixes.
+        let end = body
+            .find( | c | c == 'i' || c == 'u' || ( radix != 16 && c == 'f' ) )
+            .unwrap_or( body.len() );
+
+        ( &body[ .. end ], &body[ end .. ], false )
+    };
+
+    let suffix = match suffix {
+        "" => None,
+        s => match NumberSuffix::from_str( s ) {
+            Some( suffix ) if suffix.is_float() && radix != 10 => return Err( NumberError::InvalidSuffix( s.to_string() ) ),
+            Some( suffix ) if !suffix.is_float() && is_float => return Err( NumberError::InvalidSuffix( s.to_string() ) ),
+            Some( suffix ) => Some( suffix ),
+            None => return Err( NumberError::InvalidSuffix( s.to_string() ) ),
+        },
+    };
+
+    let digits: String = digits.chars().filter( | &c | c != '_' ).collect();
+    if digits.is_empty() {
+        return Err( NumberError::MissingDigits );
+    }
+
+    if is_float || suffix.is_some_and( NumberSuffix::is_float ) {
+        let value: f64 = digits.parse().map_err( | _ | NumberError::MissingDigits )?;
+
+        let overflow = match suffix {
+            Some( NumberSuffix::F32 ) => ( value as f32 ).is_infinite(),
+            _ => value.is_infinite(),
+        };
+        if overflow {
+            return Err( NumberError::Overflow( suffix ) );
+        }
+
+        return Ok( Number {
+            value: NumberValue::Float( value ),
+            suffix,
+        } );
+    }
+
+    if let Some( c ) = digits.chars().find( | c | !c.is_digit( radix ) ) {
+        return Err( NumberError::InvalidDigit( c ) );
+    }
+
+    let value = u64::from_str_radix( &digits, radix ).map_err( | _ | NumberError::Overflow( suffix ) )?;
+    let max = match suffix {
+        Some( suffix ) if negative => suffix.max_negative_int(),
+        Some( suffix ) => suffix.max_int(),
+        None => u64::MAX,
+    };
+    if value > max {
+        return Err( NumberError::Overflow( suffix ) );
+    }
+
+    Ok( Number {
+        value: NumberValue::Int( value ),
+        suffix,
+    } )
+}
+
+//  Splits decimal number into ( digits with fraction and exponent, suffix, is float ).
+//
+fn split_decimal( s: &str ) -> ( &str, &str, bool ) {
+    let bytes = s.as_bytes();
+    let is_digit = | i: usize | bytes.get( i ).is_some_and( | b | b.is_ascii_digit() || *b == b'_' );
+
+    let mut i = 0;
+    let mut is_float = false;
+
+    while is_digit( i ) {
+        i += 1;
+    }
+
+    if bytes.get( i ) == Some( &b'.' ) && is_digit( i + 1 ) {
+        is_float = true;
+        i += 1;
+        while is_digit( i ) {
+            i += 1;
+        }
+    }
+
+    if let Some( b'e' ) | Some( b'E' ) = bytes.get( i ) {
+        let j = match bytes.get( i + 1 ) {
+            Some( b'+' ) | Some( b'-' ) => i + 2,
+            _ => i + 1,
+        };
+        if is_digit( j ) {
+            is_float = true;
+            i = j;
+            while is_digit( i ) {
+                i += 1;
+            }
+        }
+    }
+
+    ( &s[ .. i ], &s[ i .. ], is_float )
+}
+
+#[ cfg( test ) ]
+mod tests {
+    use super::*;
+
+    fn int( value: u64, suffix: Option< NumberSuffix > ) -> Result< Number, NumberError > {
+        Ok( Number { value: NumberValue::Int( value ), suffix } )
+    }
+
+    fn float( value: f64, suffix: Option< NumberSuffix > ) -> Result< Number, NumberError > {
+        Ok( Number { value: NumberValue::Float( value ), suffix } )
+    }
+
+    #[ test ]
+    fn test_parse_number() {
+        assert_eq!( parse_number( "42", false ), int( 42, None ) );
+        assert_eq!( parse_number( "1_000_000", false ), int( 1_000_000, None ) );
+        assert_eq!( parse_number( "0xFF_ff", false ), int( 0xffff, None ) );
+        assert_eq!( parse_number( "0o777", false ), int( 0o777, None ) );
+        assert_eq!( parse_number( "0b1010_1010u8", false ), int( 0b1010_1010, Some( NumberSuffix::U8 ) ) );
+        assert_eq!( parse_number( "0xffi32", false ), int( 0xff, Some( NumberSuffix::I32 ) ) );
+
+        assert_eq!( parse_number( "1.5", false ), float( 1.5, None ) );
+        assert_eq!( parse_number( "1e10", false ), float( 1e10, None ) );
+        assert_eq!( parse_number( "2.5E-3", false ), float( 2.5e-3, None ) );
+        assert_eq!( parse_number( "1f32", false ), float( 1.0, Some( NumberSuffix::F32 ) ) );
+        assert_eq!( parse_number( "0.5_f64", false ), float( 0.5, Some( NumberSuffix::F64 ) ) );
+
+        assert_eq!( parse_number( "0x", false ), Err( NumberError::MissingDigits ) );
+        assert_eq!( parse_number( "0b102", false ), Err( NumberError::InvalidDigit( '2' ) ) );
+        assert_eq!( parse_number( "1x", false ), Err( NumberError::InvalidSuffix( "x".to_string() ) ) );
+        assert_eq!( parse_number( "1.5i32", false ), Err( NumberError::InvalidSuffix( "i32".to_string() ) ) );
+        assert_eq!( parse_number( "0x1f32", false ), int( 0x1f32, None ) );
+
+        assert_eq!( parse_number( "255u8", false ), int( 255, Some( NumberSuffix::U8 ) ) );
+        assert_eq!( parse_number( "256u8", false ), Err( NumberError::Overflow( Some( NumberSuffix::U8 ) ) ) );
+        assert_eq!( parse_number( "128i8", false ), Err( NumberError::Overflow( Some( NumberSuffix::I8 ) ) ) );
+        assert_eq!( parse_number( "128i8", true ), int( 128, Some( NumberSuffix::I8 ) ) );
+        assert_eq!( parse_number( "129i8", true ), Err( NumberError::Overflow( Some( NumberSuffix::I8 ) ) ) );
+        assert_eq!( parse_number( "256u8", true ), Err( NumberError::Overflow( Some( NumberSuffix::U8 ) ) ) );
+        assert_eq!( parse_number( "18446744073709551616", false ), Err( NumberError::Overflow( None ) ) );
+        assert_eq!( parse_number( "1e39f32", false ), Err( NumberError::Overflow( Some( NumberSuffix::F32 ) ) ) );
+        assert_eq!( parse_number( "1e400", false ), Err( NumberError::Overflow( None ) ) );
+    }
+
+}
@@ -1,12 +1,14 @@
 use crate::SyntaxNode;
-use crate::{ Lexer, Token, TokenKind, T, SyntaxKind, ParserEvent, ParserError, ParserErrorKind, token_set::TokenSet };
+use crate::{ Lexer, LexerError, Token, TokenKind, T, SyntaxKind, ParserEvent, ParserError, ParserErrorKind, token_set::TokenSet };
 use crate::parser_event::process;
 
-#[ derive( Clone, Copy ) ]
+#[ derive( Clone, Copy, PartialEq ) ]
 pub enum Skipper {
     None,
     Inline,
     Block,
+    //  Inline expression where newlines are insignificant, e.g. `${ }` of a `"""` string.
+    Multiline,
 }
 
 pub struct Marker {
@@ -36,7 +38,10 @@ impl Marker {
     }
 
     pub fn abandon( self, p: &mut Parser ) {
-        //  TODO.
+        //  Not completed start event has `SyntaxKind::None` and is ignored by `process`.
+        if self.pos == p.events.len() - 1 {
+            p.events.pop();
+        }
     }
 
 }
@@ -55,10 +60,13 @@ impl CompletedMarker {
         }
     }
 
+    pub fn kind( &self ) -> SyntaxKind {
+        self.kind
+    }
+
     pub fn precede( &self, p: &mut Parser ) -> Marker {
         let m = p.start();
-        let index = self.pos as usize;
-        match &mut p.events[ index ] {
+        match &mut p.events[ self.pos ] {
             ParserEvent::Start { forward_parent, .. } => {
                 *forward_parent = Some( m.pos - self.pos );
             }
@@ -72,11 +80,23 @@ impl CompletedMarker {
 
 //  ---------------------------------------------------------------------------------------------------------------  //
 
+const BLOCK_TRIVIA: TokenSet = TokenSet::new( &[
+    T![ ],
+    TokenKind::EOL,
+    TokenKind::Comment,
+    TokenKind::BlockComment,
+    TokenKind::DocComment,
+    TokenKind::InnerDocComment,
+] );
+
 pub struct Parser< 'a > {
 
     input: &'a str,
     pub tokens: Vec< Token< 'a > >,
 
+    //  Not yet reported lexer errors, the first one is the last.
+    lexer_errors: Vec< LexerError >,
+
     events: Vec< ParserEvent< 'a > >,
     // errors: Vec< ParserError >,
 
@@ -85,15 +105,33 @@ pub struct Parser< 'a > {
     skipper: Skipper,
     skippers: Vec< Skipper >,
 
+    //  Struct literals are not allowed in conditions of `if`, `while`, `for` and `match`,
+    //  where `{` starts the block: `if x { }`. They are allowed again inside of brackets.
+    struct_literals: bool,
+    struct_literals_stack: Vec< bool >,
+
     last_eaten_token_pos: usize,
 }
 
 impl < 'a > Parser< 'a > {
 
     pub fn new( input: &'a str ) -> Self {
+        let mut lexer = Lexer::with_indents( input );
+        let tokens = lexer.by_ref().collect();
+        let errors = lexer.errors().to_vec();
+
+        Parser::with_tokens( input, tokens, errors )
+    }
+
+    //  E.g. tokens and errors of `Lexed` from `Lexer::relex` after an edit.
+    pub fn with_tokens( input: &'a str, tokens: Vec< Token< 'a > >, mut lexer_errors: Vec< LexerError > ) -> Self {
+        lexer_errors.sort_by_key( | e | e.start );
+        lexer_errors.reverse();
+
         Parser {
             input,
-            tokens: Lexer::new( input ).collect(),
+            tokens,
+            lexer_errors,
 
             events: vec![],
             // errors: vec![],
@@ -103,12 +141,22 @@ impl < 'a > Parser< 'a > {
             skipper: Skipper::None,
             skippers: vec![],
 
+            struct_literals: true,
+            struct_literals_stack: vec![],
+
             last_eaten_token_pos: 0,
         }
     }
 
-    pub fn parse( mut self, rule: fn ( parser: &mut Parser ) -> CompletedMarker ) -> SyntaxNode< 'a > {
+    pub fn parse( self, rule: fn ( parser: &mut Parser ) -> CompletedMarker ) -> SyntaxNode< 'a > {
+        self.parse_with_errors( rule ).0
+    }
+
+    //  The tree with parser and lexer errors, both in the order of positions.
+    //
+    pub fn parse_with_errors( mut self, rule: fn ( parser: &mut Parser ) -> CompletedMarker ) -> ( SyntaxNode< 'a >, Vec< ParserError >, Vec< LexerError > ) {
         let _node = rule( &mut self );
+        self.lexer_errors( usize::MAX );
 
         process( self.events )
     }
@@ -118,8 +166,9 @@ impl < 'a > Parser< 'a > {
     }
 
     pub fn error( &mut self, kind: ParserErrorKind ) {
+        let ( start, end ) = self.nth( 0 ).map_or( ( self.input.len(), self.input.len() ), | t | ( t.start, t.end ) );
         let error = ParserEvent::Error{
-            error: ParserError::new( self.pos, kind ),
+            error: ParserError::new( start, end, kind ),
         };
         self.push_event( error );
     }
@@ -150,7 +199,7 @@ impl < 'a > Parser< 'a > {
         m.complete(self, SyntaxKind::Error );
     }
 
-    pub fn nth( &self, n: usize ) -> Option< &Token > {
+    pub fn nth( &self, n: usize ) -> Option< &Token< 'a > > {
         self.tokens.get( self.pos + n )
     }
 
@@ -166,6 +215,12 @@ impl < 'a > Parser< 'a > {
         self.nth_kind( 0 ) == kind
     }
 
+    //  Contextual keywords like `self` are lexed as `Id`.
+    //
+    pub fn at_id( &self, text: &str ) -> bool {
+        self.nth( 0 ).is_some_and( | t | t.kind == TokenKind::Id && t.text == text )
+    }
+
     pub fn at_ts( &self, ts: TokenSet ) -> bool {
         ts.contains( self.current() )
     }
@@ -178,8 +233,18 @@ impl < 'a > Parser< 'a > {
         self.at( TokenKind::EOL )
     }
 
+    //  Lexer errors are reported right before the tokens they start in.
+    //
+    fn lexer_errors( &mut self, end: usize ) {
+        while self.lexer_errors.last().is_some_and( | e | e.start < end ) {
+            let error = self.lexer_errors.pop().unwrap();
+            self.push_event( ParserEvent::LexerError { error } );
+        }
+    }
+
     pub fn mov( &mut self ) {
         let token = self.tokens[ self.pos ];
+        self.lexer_errors( token.end );
         self.push_event( ParserEvent::Token { token } );
         self.pos += 1;
     }
@@ -231,10 +296,6 @@ impl < 'a > Parser< 'a > {
         Marker::new( pos )
     }
 
-    pub fn eol( &mut self ) {
-        self.eat( TokenKind::EOL );
-    }
-
     pub fn set_skipper( &mut self, skipper: Skipper ) {
         self.skippers.push( self.skipper );
 
@@ -242,6 +303,10 @@ impl < 'a > Parser< 'a > {
         self.skip();
     }
 
+    pub fn skipper( &self ) -> Skipper {
+        self.skipper
+    }
+
     pub fn restore_skipper( &mut self ) {
         if let Some( skipper ) = self.skippers.pop() {
             self.skipper = skipper;
@@ -249,19 +314,35 @@ impl < 'a > Parser< 'a > {
         }
     }
 
+    pub fn set_struct_literals( &mut self, allowed: bool ) {
+        self.struct_literals_stack.push( self.struct_literals );
+
+        self.struct_literals = allowed;
+    }
+
+    pub fn struct_literals( &self ) -> bool {
+        self.struct_literals
+    }
+
+    pub fn restore_struct_literals( &mut self ) {
+        if let Some( allowed ) = self.struct_literals_stack.pop() {
+            self.struct_literals = allowed;
+        }
+    }
+
     pub fn skip( &mut self ) {
         match self.skipper {
             Skipper::None => {},
 
             Skipper::Inline => {
-                while self.at( T![ ] ) {
+                while self.at( T![ ] ) || self.at( TokenKind::BlockComment ) {
                     // println!( "skipped {:?}", self.kind() );
                     self.mov();
                 }
             }
 
-            Skipper::Block => {
-                while self.at( T![ ] ) || self.at( TokenKind::EOL ) || self.at( TokenKind::Comment ) {
+            Skipper::Block | Skipper::Multiline => {
+                while self.at_ts( BLOCK_TRIVIA ) {
                     // println!( "skipped {:?}", self.kind() );
                     self.mov();
                 }
@@ -3,15 +3,18 @@ use std::fmt;
 use crate::TokenKind;
 
 pub struct ParserError {
-    pos: usize,
-    kind: ParserErrorKind,
+    //  Byte range of the token where the error is found, empty at the end of input.
+    pub start: usize,
+    pub end: usize,
+    pub kind: ParserErrorKind,
 }
 
 impl ParserError {
 
-    pub fn new( pos: usize, kind: ParserErrorKind ) -> Self {
+    pub fn new( start: usize, end: usize, kind: ParserErrorKind ) -> Self {
         ParserError {
-            pos,
+            start,
+            end,
             kind,
         }
     }
@@ -21,7 +24,7 @@ impl ParserError {
 impl fmt::Debug for ParserError {
 
     fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
-        write!( f , "ERROR: {:?} at {}", self.kind, self.pos )
+        write!( f , "ERROR: {:?} at {}..{}", self.kind, self.start, self.end )
     }
 
 }
@@ -31,6 +34,12 @@ pub enum ParserErrorKind {
     ItemRequired,
     NameRequired,
     FunctionArgumentsExpected,
+    TypeRequired,
+    ExpressionRequired,
+    LoopRequired,
+    PatternRequired,
+    SelfRequired,
+    ImportPathRequired,
 }
 
 impl fmt::Debug for ParserErrorKind {
@@ -41,6 +50,12 @@ impl fmt::Debug for ParserErrorKind {
             Self::ItemRequired => write!( f, "Item required" ),
             Self::NameRequired => write!( f, "Name required" ),
             Self::FunctionArgumentsExpected => write!( f, "Function arguments expected" ),
+            Self::TypeRequired => write!( f, "Type required" ),
+            Self::ExpressionRequired => write!( f, "Expression required" ),
+            Self::LoopRequired => write!( f, "Loop required after label" ),
+            Self::PatternRequired => write!( f, "Pattern required" ),
+            Self::SelfRequired => write!( f, "`self` required after `&`" ),
+            Self::ImportPathRequired => write!( f, "Import path required" ),
         }
     }
 }
@@ -1,6 +1,6 @@
 use std::mem;
 
-use crate::{ SyntaxKind, Token, ParserError, SyntaxNode, SyntaxElement };
+use crate::{ SyntaxKind, Token, TokenKind, ParserError, LexerError, SyntaxNode, SyntaxElement };
 
 #[ derive( Debug ) ]
 pub enum ParserEvent< 'a > {
@@ -20,6 +20,10 @@ pub enum ParserEvent< 'a > {
         error: ParserError,
     },
 
+    LexerError {
+        error: LexerError,
+    },
+
 }
 
 impl < 'a > ParserEvent< 'a > {
@@ -32,8 +36,10 @@ impl < 'a > ParserEvent< 'a > {
     }
 }
 
-pub fn process( mut events: Vec< ParserEvent > ) -> SyntaxNode {
+pub fn process( mut events: Vec< ParserEvent > ) -> ( SyntaxNode, Vec< ParserError >, Vec< LexerError > ) {
     let mut forward_parents = Vec::new();
+    let mut errors = Vec::new();
+    let mut lexer_errors = Vec::new();
 
     let mut root = SyntaxNode::new( SyntaxKind::Root );
     let mut nodes: Vec< SyntaxNode > = vec![];
@@ -65,8 +71,13 @@ pub fn process( mut events: Vec< ParserEvent > ) -> SyntaxNode {
 
                 for kind in forward_parents.drain( .. ).rev() {
                     if kind != SyntaxKind::None {
+                        let mut node = SyntaxNode::new( kind );
+                        if has_doc_comments( kind ) {
+                            node.children = take_doc_comments( &mut root );
+                        }
+
                         nodes.push( root );
-                        root = SyntaxNode::new( kind );
+                        root = node;
                     }
                 }
             }
@@ -82,10 +93,61 @@ pub fn process( mut events: Vec< ParserEvent > ) -> SyntaxNode {
             }
 
             ParserEvent::Error { error } => {
-                println!( "{:?}", error );
+                errors.push( error );
+            }
+
+            ParserEvent::LexerError { error } => {
+                lexer_errors.push( error );
             }
         }
     }
 
-    root
+    ( root, errors, lexer_errors )
+}
+
+fn has_doc_comments( kind: SyntaxKind ) -> bool {
+    matches!( kind,
+        SyntaxKind::Fn | SyntaxKind::Struct | SyntaxKind::StructMethod | SyntaxKind::Enum | SyntaxKind::TypeAlias | SyntaxKind::Const |
+        SyntaxKind::Trait | SyntaxKind::TraitMethod | SyntaxKind::Impl | SyntaxKind::Import
+    )
+}
+
+//  Doc comments are skipped as trivia before the item is started, so they end up in the parent node
+//  or in the tail of the previous item (e.g. after `}` of its block).
+//  Here we move them (and all trivia after them) into the item:
+//
+//      /// Adds two numbers.
+//      // Not a doc comment, but it is moved too.
+//      fn add( x: number, y: number ) -> number { ... }
+//
+fn take_doc_comments< 'a >( parent: &mut SyntaxNode< 'a > ) -> Vec< SyntaxElement< 'a > > {
+    let is_trivia = | e: &SyntaxElement | matches!( e, SyntaxElement::Token( token ) if matches!( token.kind,
+        TokenKind::Space | TokenKind::EOL | TokenKind::Comment | TokenKind::BlockComment | TokenKind::DocComment
+    ) );
+
+    let trivia_start = parent.children
+        .iter()
+        .rposition( | e | !is_trivia( e ) )
+        .map_or( 0, | i | i + 1 );
+
+    let mut r = match trivia_start.checked_sub( 1 ).map( | i | &mut parent.children[ i ] ) {
+        Some( SyntaxElement::Node( node ) ) => take_doc_comments( node ),
+        _ => Vec::new(),
+    };
+
+    let start = if r.is_empty() {
+        parent.children[ trivia_start .. ]
+            .iter()
+            .position( | e | matches!( e, SyntaxElement::Token( token ) if token.kind == TokenKind::DocComment ) )
+            .map( | i | trivia_start + i )
+
+    } else {
+        Some( trivia_start )
+    };
+
+    if let Some( start ) = start {
+        r.append( &mut parent.children.split_off( start ) );
+    }
+
+    r
 }
@@ -0,0 +1,109 @@
+//  Lexer and parser are lossless: tokens cover the whole input and the tree keeps all of them,
+//  so the text of the tree is the input. Checked on arbitrary input:
+//
+//      cargo test round_trip
+//
+
+use proptest::prelude::*;
+
+use crate::{ Lexer, Token };
+use crate::parser::Parser;
+use crate::grammar::r_source_file;
+use crate::bench::synthetic_source;
+
+fn check_round_trip( input: &str ) {
+    for lexer in [ Lexer::new( input ), Lexer::with_indents( input ) ] {
+        let tokens: Vec< Token > = lexer.collect();
+        let text: String = tokens.iter().map( | t | t.text ).collect();
+        assert_eq!( text, input );
+    }
+
+    let node = Parser::new( input ).parse( r_source_file );
+    assert_eq!( node.text(), input );
+}
+
+//  Pieces of the syntax, so that random sequences of them get deep into the grammar.
+//
+const PIECES: &[ &str ] = &[
+    "fn", "struct", "enum", "type", "let", "if", "while", "else", "for", "in", "true", "null",
+    "return", "break", "continue", "'a", "'", "match", "=>", "_", "|x|", "fn (", "+=", "||=", "t.0.1", "const", "&self", "trait", "impl", "import {", "} from", "pub", "export",
+    "x", "Foo", "1", "1.5e3", "0x_u8",
+    " ", "    ", "\t", "\n", "\r\n", "\r",
+    "(", ")", "{", "}", "[", "]", "<", ">", ":", ":.2", ";", ",", ".", "..", "...", "->", "=", "+", "-", "!", "||", "&&", "|", "\\",
+    "\"", "\"\"\"", "${", "r\"", "r#\"", "\"#", "\\n", "\\u{",
+    "//", "///", "//!", "/*", "*/",
+    "€", "ф", "\u{0}", "\u{c}",
+];
+
+fn pieces() -> impl Strategy< Value = String > {
+    prop::collection::vec( prop::sample::select( PIECES ), 0 .. 64 )
+        .prop_map( | pieces | pieces.concat() )
+}
+
+proptest! {
+
+    #[ test ]
+    fn round_trip_bytes( bytes in prop::collection::vec( any::< u8 >(), 0 .. 256 ) ) {
+        check_round_trip( &String::from_utf8_lossy( &bytes ) );
+    }
+
+    #[ test ]
+    fn round_trip_strings( input in "\\PC*" ) {
+        check_round_trip( &input );
+    }
+
+    #[ test ]
+    fn round_trip_pieces( input in pieces() ) {
+        check_round_trip( &input );
+    }
+
+}
+
+//  Fuzzing by random edits of a valid source: replaces, inserts and removes pieces and bytes.
+//
+#[ test ]
+fn round_trip_fuzz() {
+    let source = synthetic_source( 2 );
+
+    //  xorshift64, deterministic so failures are reproducible.
+    let mut seed = 0x2545_F491_4F6C_DD1Du64;
+    let mut random = move | n: usize | {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        ( seed % n as u64 ) as usize
+    };
+
+    for _ in 0 .. 500 {
+        let mut input = source.clone();
+
+        for _ in 0 .. 1 + random( 8 ) {
+            let mut pos = random( input.len() + 1 );
+            while !input.is_char_boundary( pos ) {
+                pos -= 1;
+            }
+            let mut end = ( pos + random( 8 ) ).min( input.len() );
+            while !input.is_char_boundary( end ) {
+                end -= 1;
+            }
+
+            let piece = PIECES[ random( PIECES.len() ) ];
+            match random( 3 ) {
+                0 => input.replace_range( pos .. end, piece ),
+                1 => input.insert_str( pos, piece ),
+                _ => input.replace_range( pos .. end, "" ),
+            }
+        }
+
+        check_round_trip( &input );
+    }
+}
+
+#[ test ]
+fn round_trip_examples() {
+    check_round_trip( "" );
+    check_round_trip( &synthetic_source( 3 ) );
+    check_round_trip( "fn f( x: number ) {\n    \"a ${ x } b\n}\n}}} )\n" );
+    check_round_trip( "  /// doc\n\tfn f():\r\n  x\n    y\n z\n/* unterminated" );
+    check_round_trip( "type T = (\nstruct { fn" );
+}
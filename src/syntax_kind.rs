@@ -10,8 +10,15 @@ pub enum SyntaxKind {
 
     Name,
 
+    Visibility,
+
+    Import,
+    ImportName,
+
     TypeAlias,
 
+    Const,
+
     Type,
     TypeArray,
     TypeTuple,
@@ -31,6 +38,13 @@ pub enum SyntaxKind {
     EnumVariants,
     EnumVariant,
 
+    Trait,
+    TraitItems,
+    TraitMethod,
+
+    Impl,
+    ImplItems,
+
     TupleFields,
     TupleField,
 
@@ -40,21 +54,54 @@ pub enum SyntaxKind {
     Fn,
     Block,
     If,
+    Else,
     For,
+    Pattern,
+    WildcardPattern,
+    BindingPattern,
+    LiteralPattern,
+    TuplePattern,
+    VariantPattern,
+    RecordPattern,
+    RecordPatternField,
+    OrPattern,
+    Match,
+    Closure,
+    ClosureParams,
+    ClosureParam,
+    MatchArm,
+    MatchGuard,
     While,
+    LoopLabel,
+    Return,
+    Break,
+    Continue,
     Expr,
     Let,
 
     InlineExpr,
     InlineBinary,
     InlineUnary,
+    InlineRange,
+    InlineArray,
+    InlineArrayRepeat,
+    InlineTuple,
+    InlineIndex,
+    InlineTupleField,
+    InlineStruct,
+    InlineStructField,
+    InlineStructSpread,
+    Assign,
 
     InlineSubexpr,
     InlineNumber,
+    InlineBool,
+    InlineNull,
     InlineVar,
 
     FnParams,
     FnParam,
+    SelfParam,
     FnReturnType,
 
     CallArgs,
@@ -4,28 +4,44 @@ use crate::{ SyntaxKind, Token, TokenKind, token_set::TokenSet };
 
 //  ---------------------------------------------------------------------------------------------------------------  //
 
-type InputPos = ( usize, usize );
-type InputRange = ( InputPos, InputPos );
+pub const DEFAULT_TAB_WIDTH: usize = 4;
 
-struct ParseTree< 'a > {
-    input: &'a str,
-    root: SyntaxNode< 'a >,
+//  Zero-based line and column. Column is counted in bytes, in chars and in chars with tabs expanded.
+//
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+pub struct InputPos {
+    pub line: usize,
+    pub column: usize,
+    pub char_column: usize,
+    pub visual_column: usize,
 }
 
-impl < 'a > ParseTree < 'a > {
+pub fn get_input_pos( s: &str, pos: usize, tab_width: usize ) -> InputPos {
+    let ( column, line ) = pos_to_coords( s, pos );
+    let line_start = &s[ pos - column .. pos ];
 
-    fn get_token_range( &self, token: Token ) -> InputRange {
-        (
-            pos_to_coords( self.input, token.start ),
-            pos_to_coords( self.input, token.end )
-        )
+    InputPos {
+        line,
+        column,
+        char_column: line_start.chars().count(),
+        visual_column: self::column( line_start, tab_width ),
     }
+}
 
+//  Width of `s` (a start of a line) where tabs advance to the next multiple of `tab_width`
+//  and form feeds take no space.
+//
+pub fn column( s: &str, tab_width: usize ) -> usize {
+    s.chars().fold( 0, | column, c | match c {
+        '\t' if tab_width > 0 => ( column / tab_width + 1 ) * tab_width,
+        '\x0c' => column,
+        _ => column + 1,
+    } )
 }
 
-fn pos_to_coords( s: &str, pos: usize ) -> InputPos {
+fn pos_to_coords( s: &str, pos: usize ) -> ( usize, usize ) {
     let s = &s[ 0 .. pos ];
-    let line_ends = s.match_indices( | c | c == '\n' );
+    let line_ends = s.match_indices( '\n' );
 
     let mut y = 0;
     let mut x = 0;
@@ -37,27 +53,19 @@ fn pos_to_coords( s: &str, pos: usize ) -> InputPos {
     ( pos - x, y )
 }
 
-#[ cfg( test ) ]
-mod tests {
-    use super::*;
-
-    #[ test ]
-    fn test_pos_to_coords() {
-
-        assert_eq!( pos_to_coords( "abcd\nefgh", 0 ), ( 0, 0 ) );
-        assert_eq!( pos_to_coords( "abcd\nefgh", 2 ), ( 2, 0 ) );
-        assert_eq!( pos_to_coords( "abcd\nefgh", 4 ), ( 4, 0 ) );
-        assert_eq!( pos_to_coords( "abcd\nefgh", 5 ), ( 0, 1 ) );
-        assert_eq!( pos_to_coords( "abcd\nefgh\n", 9 ), ( 4, 1 ) );
-        assert_eq!( pos_to_coords( "abcd\nefgh\nijkl", 10 ), ( 0, 2 ) );
-        assert_eq!( pos_to_coords( "abcd\nefgh\nijkl", 12 ), ( 2, 2 ) );
-
-    }
-
-}
-
 //  ---------------------------------------------------------------------------------------------------------------  //
 
+const TRIVIA: TokenSet = TokenSet::new( &[
+    TokenKind::Space,
+    TokenKind::EOL,
+    TokenKind::Indent,
+    TokenKind::Dedent,
+    TokenKind::Comment,
+    TokenKind::BlockComment,
+    TokenKind::DocComment,
+    TokenKind::InnerDocComment,
+] );
+
 pub enum SyntaxElement< 'a > {
     Node( SyntaxNode< 'a > ),
     Token( Token< 'a > ),
@@ -81,6 +89,35 @@ impl < 'a > SyntaxNode< 'a > {
         self.children.push( element );
     }
 
+    //  Child nodes, without tokens.
+    //
+    pub fn nodes( &'a self ) -> impl DoubleEndedIterator< Item = &'a SyntaxNode< 'a > > {
+        self.children
+            .iter()
+            .filter_map( | e | match e {
+                SyntaxElement::Node( node ) => Some( node ),
+                _ => None,
+            } )
+    }
+
+    //  The node and its descendants of the `kind`, in the source order.
+    //
+    pub fn descendants( &'a self, kind: SyntaxKind ) -> Vec< &'a SyntaxNode< 'a > > {
+        let mut nodes = Vec::new();
+        self.collect_descendants( kind, &mut nodes );
+
+        nodes
+    }
+
+    fn collect_descendants( &'a self, kind: SyntaxKind, nodes: &mut Vec< &'a SyntaxNode< 'a > > ) {
+        if self.kind == kind {
+            nodes.push( self );
+        }
+        for child in self.nodes() {
+            child.collect_descendants( kind, nodes );
+        }
+    }
+
     pub fn find_nodes( &'a self, kind: SyntaxKind ) -> Vec< &'a SyntaxNode< 'a > > {
         self.children
             .iter()
@@ -111,6 +148,31 @@ impl < 'a > SyntaxNode< 'a > {
             .nth( 0 )
     }
 
+    //  Text of `///` (or `//!` for `inner`) comments of the node, one line per comment.
+    //
+    pub fn docs( &self, inner: bool ) -> Option< String > {
+        let ( kind, prefix ) = if inner { ( TokenKind::InnerDocComment, "//!" ) } else { ( TokenKind::DocComment, "///" ) };
+
+        let lines: Vec< &str > = self.children
+            .iter()
+            .filter_map( | e | match e {
+                SyntaxElement::Token( token ) if token.kind == kind => Some( token.text ),
+                _ => None,
+            } )
+            .map( | text | {
+                let text = &text[ prefix.len() .. ];
+                text.strip_prefix( ' ' ).unwrap_or( text )
+            } )
+            .collect();
+
+        if lines.is_empty() {
+            None
+
+        } else {
+            Some( lines.join( "\n" ) )
+        }
+    }
+
     pub fn find_token_in_set( &'a self, ts: TokenSet ) -> Option< &'a Token< 'a > > {
         self.children
             .iter()
@@ -121,6 +183,66 @@ impl < 'a > SyntaxNode< 'a > {
             .nth( 0 )
     }
 
+    //  `add` of `fn add()`, the identifier of the `Name` child.
+    //
+    pub fn name( &'a self ) -> Option< &'a str > {
+        self.find_node_by_index( SyntaxKind::Name, 0 )?
+            .find_token( TokenKind::Id )
+            .map( | token | token.text )
+    }
+
+    //  Tokens of the node and of its descendants, without trivia.
+    //
+    pub fn tokens( &self ) -> Vec< &Token< 'a > > {
+        let mut tokens = Vec::new();
+        self.collect_tokens( &mut tokens );
+        tokens.retain( | token | !TRIVIA.contains( token.kind ) );
+
+        tokens
+    }
+
+    //  Byte range of the node without leading and trailing trivia.
+    //
+    pub fn range( &self ) -> Option< ( usize, usize ) > {
+        let tokens = self.tokens();
+        let first = tokens.first()?;
+        let last = tokens.last()?;
+
+        Some( ( first.start, last.end ) )
+    }
+
+    fn collect_tokens< 'b >( &'b self, tokens: &mut Vec< &'b Token< 'a > > ) {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node( node ) => node.collect_tokens( tokens ),
+                SyntaxElement::Token( token ) => tokens.push( token ),
+            }
+        }
+    }
+
+    //  Source text of the node including trivia. The text of the root node is the whole input.
+    //
+    pub fn text( &self ) -> String {
+        self.to_string()
+    }
+
+    fn write_text( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node( node ) => node.write_text( f )?,
+                SyntaxElement::Token( token ) => f.write_str( token.text )?,
+            }
+        }
+
+        Ok( () )
+    }
+
+}
+
+impl < 'a > fmt::Display for SyntaxNode< 'a > {
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        self.write_text( f )
+    }
 }
 
 impl < 'a >fmt::Debug for SyntaxNode< 'a > {
@@ -151,3 +273,35 @@ fn format_children( node: &SyntaxNode, indent: usize ) -> String {
         .collect::< Vec< String> >()
         .join( "\n" )
 }
+
+#[ cfg( test ) ]
+mod tests {
+    use super::*;
+
+    #[ test ]
+    fn test_pos_to_coords() {
+
+        assert_eq!( pos_to_coords( "abcd\nefgh", 0 ), ( 0, 0 ) );
+        assert_eq!( pos_to_coords( "abcd\nefgh", 2 ), ( 2, 0 ) );
+        assert_eq!( pos_to_coords( "abcd\nefgh", 4 ), ( 4, 0 ) );
+        assert_eq!( pos_to_coords( "abcd\nefgh", 5 ), ( 0, 1 ) );
+        assert_eq!( pos_to_coords( "abcd\nefgh\n", 9 ), ( 4, 1 ) );
+        assert_eq!( pos_to_coords( "abcd\nefgh\nijkl", 10 ), ( 0, 2 ) );
+        assert_eq!( pos_to_coords( "abcd\nefgh\nijkl", 12 ), ( 2, 2 ) );
+
+    }
+
+    #[ test ]
+    fn test_get_input_pos() {
+
+        assert_eq!( get_input_pos( "abcd\nefgh", 7, 4 ), InputPos { line: 1, column: 2, char_column: 2, visual_column: 2 } );
+        assert_eq!( get_input_pos( "пр\nиветмир", 7, 4 ), InputPos { line: 1, column: 2, char_column: 1, visual_column: 1 } );
+        assert_eq!( get_input_pos( "пр\nиветмир", 11, 4 ), InputPos { line: 1, column: 6, char_column: 3, visual_column: 3 } );
+
+        assert_eq!( get_input_pos( "a\r\n\tb\tc", 5, 4 ), InputPos { line: 1, column: 2, char_column: 2, visual_column: 5 } );
+        assert_eq!( get_input_pos( "a\r\n\tb\tc", 6, 8 ), InputPos { line: 1, column: 3, char_column: 3, visual_column: 16 } );
+        assert_eq!( get_input_pos( "  \tx", 3, 4 ).visual_column, 4 );
+
+    }
+
+}
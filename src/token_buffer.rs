@@ -0,0 +1,104 @@
+use crate::{ Lexer, Token, TokenKind };
+
+//  Compact storage of tokens: kinds and offsets in separate arrays, 5 bytes per token instead of
+//  the 40 bytes of `Token`. Tokens cover the input without gaps, so the end of a token is the start
+//  of the next one.
+//
+pub struct TokenBuffer< 'a > {
+    input: &'a str,
+    kinds: Vec< TokenKind >,
+    //  One more than `kinds`, the last one is the end of the input.
+    starts: Vec< u32 >,
+}
+
+impl < 'a > TokenBuffer< 'a > {
+
+    pub fn new( lexer: &mut Lexer< 'a > ) -> Self {
+        let input = lexer.input();
+        assert!( input.len() <= u32::MAX as usize, "Input is too large for TokenBuffer" );
+
+        //  Roughly one token per 4 bytes of a typical source.
+        let capacity = input.len() / 4 + 1;
+        let mut kinds = Vec::with_capacity( capacity );
+        let mut starts = Vec::with_capacity( capacity + 1 );
+
+        for token in lexer {
+            debug_assert!( starts.last().is_none_or( | &start | start as usize <= token.start ) );
+            kinds.push( token.kind );
+            starts.push( token.start as u32 );
+        }
+        starts.push( input.len() as u32 );
+
+        TokenBuffer {
+            input,
+            kinds,
+            starts,
+        }
+    }
+
+    pub fn len( &self ) -> usize {
+        self.kinds.len()
+    }
+
+    pub fn is_empty( &self ) -> bool {
+        self.kinds.is_empty()
+    }
+
+    pub fn kind( &self, i: usize ) -> TokenKind {
+        self.kinds[ i ]
+    }
+
+    pub fn kinds( &self ) -> &[ TokenKind ] {
+        &self.kinds
+    }
+
+    pub fn start( &self, i: usize ) -> usize {
+        self.starts[ i ] as usize
+    }
+
+    pub fn end( &self, i: usize ) -> usize {
+        self.starts[ i + 1 ] as usize
+    }
+
+    pub fn text( &self, i: usize ) -> &'a str {
+        &self.input[ self.start( i ) .. self.end( i ) ]
+    }
+
+    pub fn get( &self, i: usize ) -> Option< Token< 'a > > {
+        if i >= self.len() {
+            return None;
+        }
+
+        Some( Token {
+            kind: self.kind( i ),
+            text: self.text( i ),
+            start: self.start( i ),
+            end: self.end( i ),
+        } )
+    }
+
+    pub fn iter( &self ) -> impl Iterator< Item = Token< 'a > > + '_ {
+        ( 0 .. self.len() ).map( | i | self.get( i ).unwrap() )
+    }
+
+}
+
+#[ cfg( test ) ]
+mod tests {
+    use super::*;
+
+    #[ test ]
+    fn token_buffer() {
+        let input = "fn f():\n    let s = \"a ${ x } b\" /* c */\n    g( 1.5 )\n";
+
+        let tokens: Vec< Token > = Lexer::with_indents( input ).collect();
+        let buffer = TokenBuffer::new( &mut Lexer::with_indents( input ) );
+
+        assert_eq!( buffer.len(), tokens.len() );
+        for ( a, b ) in tokens.iter().zip( buffer.iter() ) {
+            assert_eq!( ( a.kind, a.text, a.start, a.end ), ( b.kind, b.text, b.start, b.end ) );
+        }
+        assert!( buffer.get( tokens.len() ).is_none() );
+    }
+
+}
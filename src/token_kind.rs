@@ -29,7 +29,7 @@ pub enum TokenKind {
     Backslash,
     CloseBracket,
     Caret,
-    //  Underscore,
+    Underscore,
     Backtick,
     OpenBrace,
     Pipe,
@@ -49,6 +49,7 @@ pub enum TokenKind {
     SlashEq,
     LtEq,
     EqEq,
+    EqGt,
     GtEq,
     PipePipe,
     PipePipeEq,
@@ -58,10 +59,18 @@ pub enum TokenKind {
     Indent,
     Dedent,
     Comment,
+    BlockComment,
+    DocComment,
+    InnerDocComment,
     Id,
+    //  `'outer` of labeled loops.
+    Label,
     Number,
     StringFragment,
+    RawString,
     DollarOpenBrace,
+    FormatSpec,
+    TripleQuote,
 
     Type,
     Struct,
@@ -72,7 +81,23 @@ pub enum TokenKind {
     If,
     For,
     While,
+    Else,
+    Return,
+    Break,
+    Continue,
+    Match,
+    In,
+    True,
+    False,
+    Null,
+    Import,
+    Export,
+    Pub,
+    Impl,
+    Trait,
+    As,
 
+    //  Must be the last one, see `TokenSet`.
     Unknown,
 }
 
@@ -106,6 +131,7 @@ macro_rules !T {
     [ '\\' ] => { TokenKind::Backslash };
     [ ']' ] => { TokenKind::CloseBracket };
     [ ^ ] => { TokenKind::Caret };
+    [ _ ] => { TokenKind::Underscore };
     [ '`' ] => { TokenKind::Backtick };
     [ '{' ] => { TokenKind::OpenBrace };
     [ | ] => { TokenKind::Pipe };
@@ -125,11 +151,13 @@ macro_rules !T {
     [ /= ] => { TokenKind::SlashEq };
     [ <= ] => { TokenKind::LtEq };
     [ == ] => { TokenKind::EqEq };
+    [ => ] => { TokenKind::EqGt };
     [ >= ] => { TokenKind::GtEq };
     [ || ] => { TokenKind::PipePipe };
     [ ||= ] => { TokenKind::PipePipeEq };
 
     [ "${" ] => { TokenKind::DollarOpenBrace };
+    [ "\"\"\"" ] => { TokenKind::TripleQuote };
 
     [ type ] => { TokenKind::Type };
     [ struct ] => { TokenKind::Struct };
@@ -140,6 +168,21 @@ macro_rules !T {
     [ for ] => { TokenKind::For };
     [ while ] => { TokenKind::While };
     [ fn ] => { TokenKind::Fn };
+    [ else ] => { TokenKind::Else };
+    [ return ] => { TokenKind::Return };
+    [ break ] => { TokenKind::Break };
+    [ continue ] => { TokenKind::Continue };
+    [ match ] => { TokenKind::Match };
+    [ in ] => { TokenKind::In };
+    [ true ] => { TokenKind::True };
+    [ false ] => { TokenKind::False };
+    [ null ] => { TokenKind::Null };
+    [ import ] => { TokenKind::Import };
+    [ export ] => { TokenKind::Export };
+    [ pub ] => { TokenKind::Pub };
+    [ impl ] => { TokenKind::Impl };
+    [ trait ] => { TokenKind::Trait };
+    [ as ] => { TokenKind::As };
 
 }
 pub use T;
@@ -1,40 +1,57 @@
 use crate::TokenKind;
 
+//  Bitset of token kinds, one bit per kind.
+//
+const N_WORDS: usize = TokenKind::Unknown as usize / 64 + 1;
+
 #[ derive( Copy, Clone ) ]
 pub struct TokenSet {
-    bits: u128,
+    words: [ u64; N_WORDS ],
 }
 
 impl TokenSet {
 
     pub const fn new( kinds: &[ TokenKind ] ) -> TokenSet {
-        let mut bits = 0u128;
+        let mut words = [ 0u64; N_WORDS ];
 
         let mut i = 0;
         while i < kinds.len() {
-            bits |= mask( kinds[ i ] );
+            let ( word, mask ) = mask( kinds[ i ] );
+            words[ word ] |= mask;
             i += 1;
         }
 
         TokenSet {
-            bits,
+            words,
         }
     }
 
     pub const fn contains( &self, kind: TokenKind ) -> bool {
-        self.bits & mask( kind ) != 0
+        let ( word, mask ) = mask( kind );
+
+        self.words[ word ] & mask != 0
     }
 
     pub const fn union( self, other: TokenSet ) -> TokenSet {
+        let mut words = self.words;
+
+        let mut i = 0;
+        while i < N_WORDS {
+            words[ i ] |= other.words[ i ];
+            i += 1;
+        }
+
         TokenSet {
-            bits: self.bits | other.bits,
+            words,
         }
     }
 
 }
 
-const fn mask( kind: TokenKind ) -> u128 {
-    1 << ( kind as usize )
+const fn mask( kind: TokenKind ) -> ( usize, u64 ) {
+    let i = kind as usize;
+
+    ( i / 64, 1 << ( i % 64 ) )
 }
 
 #[ cfg( test ) ]
@@ -59,4 +76,14 @@ mod tests {
         assert!( !set_2.contains( TokenKind::Space ) );
     }
 
+    #[ test ]
+    fn token_set_many_kinds() {
+        let set = TokenSet::new( &[ TokenKind::Space, TokenKind::As, TokenKind::Unknown ] );
+        assert!( set.contains( TokenKind::Space ) );
+        assert!( set.contains( TokenKind::As ) );
+        assert!( set.contains( TokenKind::Unknown ) );
+        assert!( !set.contains( TokenKind::Trait ) );
+        assert!( !set.contains( TokenKind::Bang ) );
+    }
+
 }
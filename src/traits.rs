@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{ SyntaxNode, SyntaxKind, TokenKind };
+
+//  Conformance of `impl Trait for Type` blocks to trait declarations:
+//
+//      trait Show< T > {
+//          fn show( &self, x: T ) -> string;
+//          fn print( &self ) { ... }
+//      }
+//
+//      impl Show< number > for Point {
+//          fn show( &self, x: number ) -> string { ... }
+//      }
+//
+//  Every method of the trait without a default has to be defined with the same signature, trait generic
+//  params and `Self` replaced by the impl args and target. Types are compared by their text.
+//
+
+#[ derive( Clone ) ]
+pub struct TraitError {
+    pub start: usize,
+    pub end: usize,
+    pub kind: TraitErrorKind,
+}
+
+impl fmt::Debug for TraitError {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        write!( f , "ERROR: {:?} at {}..{}", self.kind, self.start, self.end )
+    }
+
+}
+
+#[ derive( Clone, PartialEq ) ]
+pub enum TraitErrorKind {
+    UnknownTrait( String ),
+    //  Trait, expected and found number of generic args.
+    GenericArgCount( String, usize, usize ),
+    //  Trait, method.
+    MissingMethod( String, String ),
+    NotATraitMethod( String, String ),
+    //  Method, expected and found signatures.
+    SignatureMismatch( String, String, String ),
+    //  Trait, type.
+    ConflictingImpl( String, String ),
+    //  Type, method defined twice for the type by the struct or impls.
+    ConflictingMethod( String, String ),
+}
+
+impl fmt::Debug for TraitErrorKind {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match self {
+            Self::UnknownTrait( name ) => write!( f, "Unknown trait {}", name ),
+            Self::GenericArgCount( name, expected, found ) => write!( f, "Trait {} expects {} generic args, found {}", name, expected, found ),
+            Self::MissingMethod( name, method ) => write!( f, "Method {} of trait {} is not implemented", method, name ),
+            Self::NotATraitMethod( name, method ) => write!( f, "Method {} is not a member of trait {}", method, name ),
+            Self::SignatureMismatch( method, expected, found ) => write!( f, "Method {} has signature {}, expected {}", method, found, expected ),
+            Self::ConflictingImpl( name, ty ) => write!( f, "Conflicting impls of trait {} for {}", name, ty ),
+            Self::ConflictingMethod( ty, method ) => write!( f, "Method {} is already defined for {}", method, ty ),
+        }
+    }
+
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+pub fn check_traits( root: &SyntaxNode ) -> Vec< TraitError > {
+    let mut traits = HashMap::new();
+    let mut impls = Vec::new();
+    let mut structs = Vec::new();
+    collect_items( root, &mut traits, &mut impls, &mut structs );
+
+    let mut errors = Vec::new();
+
+    //  Methods of every type, the first definition wins.
+    let mut methods: HashMap< String, Vec< &str > > = HashMap::new();
+    for node in structs {
+        if let ( Some( name ), Some( items ) ) = ( node.name(), node.find_node_by_index( SyntaxKind::StructItems, 0 ) ) {
+            let names = methods.entry( name.to_string() ).or_default();
+            for method in items.find_nodes( SyntaxKind::StructMethod ) {
+                if let Some( method ) = method.name() {
+                    names.push( method );
+                }
+            }
+        }
+    }
+
+    let mut seen_impls = Vec::new();
+
+    for node in impls {
+        let Some( trait_ref ) = node.find_node_by_index( SyntaxKind::TypeRef, 0 ) else {
+            continue;
+        };
+        let Some( trait_name ) = trait_ref.find_token( TokenKind::Id ).map( | token | token.text ) else {
+            continue;
+        };
+        let target = node.find_node_by_index( SyntaxKind::Type, 0 ).map_or( String::new(), | ty | type_text( ty, &HashMap::new() ) );
+
+        let Some( &trait_node ) = traits.get( trait_name ) else {
+            errors.push( error( trait_ref, TraitErrorKind::UnknownTrait( trait_name.to_string() ) ) );
+            continue;
+        };
+
+        let key = ( type_text( trait_ref, &HashMap::new() ), target.clone() );
+        if seen_impls.contains( &key ) {
+            errors.push( error( trait_ref, TraitErrorKind::ConflictingImpl( trait_name.to_string(), target ) ) );
+            continue;
+        }
+        seen_impls.push( key );
+
+        //  Trait generic params and `Self` by the impl args and target.
+        let params: Vec< &str > = trait_node
+            .find_node_by_index( SyntaxKind::GenericParams, 0 )
+            .map( | params | params.find_nodes( SyntaxKind::GenericParam ).into_iter().filter_map( | param | param.name() ).collect() )
+            .unwrap_or_default();
+        let args: Vec< String > = trait_ref
+            .find_node_by_index( SyntaxKind::GenericArgs, 0 )
+            .map( | args | args.find_nodes( SyntaxKind::GenericArg ).into_iter().map( | arg | type_text( arg, &HashMap::new() ) ).collect() )
+            .unwrap_or_default();
+
+        if params.len() != args.len() {
+            errors.push( error( trait_ref, TraitErrorKind::GenericArgCount( trait_name.to_string(), params.len(), args.len() ) ) );
+            continue;
+        }
+
+        let mut subst: HashMap< &str, String > = params.into_iter().zip( args ).collect();
+        subst.insert( "Self", target.clone() );
+
+        let trait_methods: Vec< &SyntaxNode > = trait_node
+            .find_node_by_index( SyntaxKind::TraitItems, 0 )
+            .map( | items | items.find_nodes( SyntaxKind::TraitMethod ) )
+            .unwrap_or_default();
+        let impl_methods: Vec< &SyntaxNode > = node
+            .find_node_by_index( SyntaxKind::ImplItems, 0 )
+            .map( | items | items.find_nodes( SyntaxKind::StructMethod ) )
+            .unwrap_or_default();
+
+        let type_methods = methods.entry( target.clone() ).or_default();
+
+        for method in &impl_methods {
+            let Some( method_name ) = method.find_node_by_index( SyntaxKind::Name, 0 ) else {
+                continue;
+            };
+            let name = method.name().unwrap_or( "" );
+
+            let Some( expected ) = trait_methods.iter().find( | x | x.name() == Some( name ) ) else {
+                errors.push( error( method_name, TraitErrorKind::NotATraitMethod( trait_name.to_string(), name.to_string() ) ) );
+                continue;
+            };
+
+            let expected = Signature::new( expected, &subst );
+            let found = Signature::new( method, &HashMap::from( [ ( "Self", target.clone() ) ] ) );
+            if expected != found {
+                errors.push( error( method_name, TraitErrorKind::SignatureMismatch( name.to_string(), expected.to_string(), found.to_string() ) ) );
+            }
+
+            if type_methods.contains( &name ) {
+                errors.push( error( method_name, TraitErrorKind::ConflictingMethod( target.clone(), name.to_string() ) ) );
+            } else {
+                type_methods.push( name );
+            }
+        }
+
+        for method in &trait_methods {
+            let name = method.name().unwrap_or( "" );
+            if impl_methods.iter().any( | x | x.name() == Some( name ) ) {
+                continue;
+            }
+
+            if method.find_node_by_index( SyntaxKind::Block, 0 ).is_none() {
+                errors.push( error( trait_ref, TraitErrorKind::MissingMethod( trait_name.to_string(), name.to_string() ) ) );
+
+            //  Default methods are defined for the type too.
+            } else if type_methods.contains( &name ) {
+                errors.push( error( trait_ref, TraitErrorKind::ConflictingMethod( target.clone(), name.to_string() ) ) );
+
+            } else {
+                type_methods.push( name );
+            }
+        }
+    }
+
+    errors
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+//  Receiver, param types and return type of a method.
+//
+#[ derive( PartialEq ) ]
+struct Signature {
+    //  `Some( true )` for `&self`.
+    receiver: Option< bool >,
+    params: Vec< String >,
+    return_type: Option< String >,
+}
+
+impl Signature {
+
+    fn new( method: &SyntaxNode, subst: &HashMap< &str, String > ) -> Self {
+        let params = method.find_node_by_index( SyntaxKind::FnParams, 0 );
+
+        Signature {
+            receiver: params
+                .and_then( | params | params.find_node_by_index( SyntaxKind::SelfParam, 0 ) )
+                .map( | param | param.find_token( TokenKind::Amp ).is_some() ),
+            params: params
+                .map( | params | params.find_nodes( SyntaxKind::FnParam ) )
+                .unwrap_or_default()
+                .into_iter()
+                .map( | param | param.find_node_by_index( SyntaxKind::Type, 0 ).map_or( "_".to_string(), | ty | type_text( ty, subst ) ) )
+                .collect(),
+            return_type: method
+                .find_node_by_index( SyntaxKind::FnReturnType, 0 )
+                .and_then( | ty | ty.find_node_by_index( SyntaxKind::Type, 0 ) )
+                .map( | ty | type_text( ty, subst ) ),
+        }
+    }
+
+}
+
+//  `( &self, number ) -> string`
+//
+impl fmt::Display for Signature {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        let mut params = Vec::new();
+        match self.receiver {
+            Some( true ) => params.push( "&self" ),
+            Some( false ) => params.push( "self" ),
+            None => {},
+        }
+        params.extend( self.params.iter().map( | x | x.as_str() ) );
+
+        if params.is_empty() {
+            write!( f, "()" )?;
+        } else {
+            write!( f, "( {} )", params.join( ", " ) )?;
+        }
+        if let Some( ty ) = &self.return_type {
+            write!( f, " -> {}", ty )?;
+        }
+
+        Ok( () )
+    }
+
+}
+
+//  Normalized text of a type, `[ T; 4 ]`, `Map< string, T >`, with the substitutions of names.
+//
+fn type_text( node: &SyntaxNode, subst: &HashMap< &str, String > ) -> String {
+    let mut text = String::new();
+    let mut prev = TokenKind::None;
+    for token in node.tokens() {
+        let space = !matches!( prev, TokenKind::None ) && !matches!( token.kind, TokenKind::Comma | TokenKind::Semicolon | TokenKind::Lt );
+        if space {
+            text.push( ' ' );
+        }
+
+        match subst.get( token.text ) {
+            Some( value ) if token.kind == TokenKind::Id => text.push_str( value ),
+            _ => text.push_str( token.text ),
+        }
+        prev = token.kind;
+    }
+
+    text
+}
+
+fn error( node: &SyntaxNode, kind: TraitErrorKind ) -> TraitError {
+    let ( start, end ) = node.range().unwrap_or( ( 0, 0 ) );
+
+    TraitError {
+        start,
+        end,
+        kind,
+    }
+}
+
+fn collect_items< 'a >(
+    node: &'a SyntaxNode< 'a >,
+    traits: &mut HashMap< &'a str, &'a SyntaxNode< 'a > >,
+    impls: &mut Vec< &'a SyntaxNode< 'a > >,
+    structs: &mut Vec< &'a SyntaxNode< 'a > >,
+) {
+    for child in node.nodes() {
+        match child.kind {
+            SyntaxKind::Trait => {
+                if let Some( name ) = child.name() {
+                    traits.entry( name ).or_insert( child );
+                }
+            }
+            SyntaxKind::Impl => impls.push( child ),
+            SyntaxKind::Struct => structs.push( child ),
+            _ => collect_items( child, traits, impls, structs ),
+        }
+    }
+}
+
+#[ cfg( test ) ]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::grammar::r_source_file;
+
+    const SHOW: &str = "\
+trait Show< T > {
+    fn show( &self, x: T ) -> string;
+    fn clone( self ) -> Self;
+    fn print( &self ) { print( self.show() ) }
+}
+struct Point {
+    x: number,
+    fn len( &self ) -> number { self.x }
+}
+";
+
+    fn errors( input: &str ) -> Vec< ( TraitErrorKind, String ) > {
+        let input = format!( "{}{}", SHOW, input );
+        let node = Parser::new( &input ).parse( r_source_file );
+
+        check_traits( &node )
+            .into_iter()
+            .map( | e | ( e.kind, input[ e.start .. e.end ].to_string() ) )
+            .collect()
+    }
+
+    #[ test ]
+    fn conformance() {
+        assert_eq!( errors( "\
+impl Show< number > for Point {
+    fn show( &self, x: number ) -> string { \"\" }
+    fn clone( self ) -> Point { self }
+}
+enum Color {}
+impl Show< [ string; 2 ] > for Color {
+    fn show( &self, x: [ string; 2 ] ) -> string { x[ 0 ] }
+    fn clone( self ) -> Self { self }
+    fn print( &self ) {}
+}
+" ), vec![] );
+    }
+
+    #[ test ]
+    fn errors_in_impls() {
+        assert_eq!( errors( "\
+impl Display for Point {}
+impl Show for Point {}
+impl Show< number > for Point {
+    fn show( self, x: string ) -> string { \"\" }
+    fn len( &self ) -> number { 0 }
+}
+impl Show< number > for Point {}
+impl Show< string > for Point {
+    fn show( &self, x: string ) -> string { x }
+    fn clone( self ) -> Point { self }
+}
+" ), vec![
+            ( TraitErrorKind::UnknownTrait( "Display".to_string() ), "Display".to_string() ),
+            ( TraitErrorKind::GenericArgCount( "Show".to_string(), 1, 0 ), "Show".to_string() ),
+            ( TraitErrorKind::SignatureMismatch( "show".to_string(), "( &self, number ) -> string".to_string(), "( self, string ) -> string".to_string() ), "show".to_string() ),
+            ( TraitErrorKind::NotATraitMethod( "Show".to_string(), "len".to_string() ), "len".to_string() ),
+            ( TraitErrorKind::MissingMethod( "Show".to_string(), "clone".to_string() ), "Show< number >".to_string() ),
+            ( TraitErrorKind::ConflictingImpl( "Show".to_string(), "Point".to_string() ), "Show< number >".to_string() ),
+            //  Already defined by `Show< number >`, `clone` isn't.
+            ( TraitErrorKind::ConflictingMethod( "Point".to_string(), "show".to_string() ), "show".to_string() ),
+            ( TraitErrorKind::ConflictingMethod( "Point".to_string(), "print".to_string() ), "Show< string >".to_string() ),
+        ] );
+    }
+
+}
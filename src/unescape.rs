@@ -0,0 +1,251 @@
+use std::borrow::Cow;
+
+use crate::LexerErrorKind;
+
+//  Decodes escape sequences of a string fragment:
+//
+//      \"  \\  \n  \t  \r  \0  \$  \u{7FFF}
+//
+//  Invalid escapes are reported via `on_error( start, end, kind )` (offsets are relative to `text`)
+//  and are kept in the result as is.
+//
+pub fn unescape< 'a >( text: &'a str, mut on_error: impl FnMut( usize, usize, LexerErrorKind ) ) -> Cow< 'a, str > {
+    if !text.contains( '\\' ) {
+        return Cow::Borrowed( text );
+    }
+
+    let mut r = String::with_capacity( text.len() );
+
+    let mut chars = text.char_indices().peekable();
+    while let Some( ( start, c ) ) = chars.next() {
+        if c != '\\' {
+            r.push( c );
+            continue;
+        }
+
+        let c = match chars.next() {
+            Some( ( _, c ) ) => c,
+            None => {
+                on_error( start, text.len(), LexerErrorKind::InvalidEscape( None ) );
+                r.push( '\\' );
+                break;
+            }
+        };
+
+        match c {
+            '"' | '\\' | '$' => r.push( c ),
+            'n' => r.push( '\n' ),
+            't' => r.push( '\t' ),
+            'r' => r.push( '\r' ),
+            '0' => r.push( '\0' ),
+
+            'u' => {
+                let mut end = start + 2;
+                let mut code: Option< u32 > = None;
+
+                if let Some( ( _, '{' ) ) = chars.peek() {
+                    chars.next();
+                    end += 1;
+
+                    let mut n_digits = 0;
+                    let mut value = 0u32;
+                    let mut closed = false;
+                    while let Some( &( i, c ) ) = chars.peek() {
+                        if c == '}' {
+                            chars.next();
+                            end = i + 1;
+                            closed = true;
+                            break;
+                        }
+                        match c.to_digit( 16 ) {
+                            Some( digit ) if n_digits < 6 => {
+                                chars.next();
+                                end = i + 1;
+                                n_digits += 1;
+                                value = value * 16 + digit;
+                            }
+                            _ => break,
+                        }
+                    }
+
+                    if closed && n_digits > 0 {
+                        code = Some( value );
+                    }
+                }
+
+                match code.and_then( char::from_u32 ) {
+                    Some( c ) => r.push( c ),
+                    None => {
+                        on_error( start, end, LexerErrorKind::InvalidUnicodeEscape );
+                        r.push_str( &text[ start .. end ] );
+                    }
+                }
+            }
+
+            _ => {
+                let end = start + 1 + c.len_utf8();
+                on_error( start, end, LexerErrorKind::InvalidEscape( Some( c ) ) );
+                r.push_str( &text[ start .. end ] );
+            }
+        }
+    }
+
+    Cow::Owned( r )
+}
+
+//  Removes common indentation of a `"""` string and its first and last lines if they are blank:
+//
+//      let s = """
+//          Hello,
+//            ${ name }!
+//          """
+//
+//  is "Hello,\n  ${ name }!". Parts are fragments of the string, `None` stands for `${ }`.
+//  `\r\n` line endings become `\n`.
+//
+pub fn strip_indent< 'a >( parts: &[ Option< &'a str > ] ) -> Vec< Option< Cow< 'a, str > > > {
+    let is_space = | c: char | c == ' ' || c == '\t' || c == '\r';
+
+    let mut indent: Option< usize > = None;
+    for ( i, part ) in parts.iter().enumerate() {
+        let Some( part ) = part else { continue };
+
+        for ( pos, _ ) in part.match_indices( '\n' ) {
+            let rest = &part[ pos + 1 .. ];
+            let line = rest.split( '\n' ).next().unwrap();
+            let n_spaces = line.len() - line.trim_start_matches( is_space ).len();
+
+            //  Line ending with `${` is not blank.
+            let is_blank = n_spaces == line.len() && ( line.len() < rest.len() || i == parts.len() - 1 );
+            if !is_blank {
+                indent = Some( indent.map_or( n_spaces, | x | x.min( n_spaces ) ) );
+            }
+        }
+    }
+    let indent = indent.unwrap_or( 0 );
+
+    let last = parts.len().wrapping_sub( 1 );
+
+    parts
+        .iter()
+        .enumerate()
+        .map( | ( i, part ) | part.map( | part | {
+            let mut s = Cow::Borrowed( part );
+
+            if indent > 0 && part.contains( '\n' ) {
+                let mut r = String::with_capacity( part.len() );
+                for ( n, line ) in part.split( '\n' ).enumerate() {
+                    if n > 0 {
+                        r.push( '\n' );
+                        let n_spaces = line.len() - line.trim_start_matches( is_space ).len();
+                        r.push_str( &line[ n_spaces.min( indent ) .. ] );
+
+                    } else {
+                        r.push_str( line );
+                    }
+                }
+                s = Cow::Owned( r );
+            }
+
+            if i == last {
+                if let Some( pos ) = s.rfind( '\n' ) {
+                    if s[ pos + 1 .. ].chars().all( is_space ) {
+                        let end = if s[ .. pos ].ends_with( '\r' ) { pos - 1 } else { pos };
+                        s = cut( s, 0, end );
+                    }
+                }
+            }
+
+            if i == 0 {
+                let n_spaces = s.len() - s.trim_start_matches( is_space ).len();
+                if s[ n_spaces .. ].starts_with( '\n' ) {
+                    let len = s.len();
+                    s = cut( s, n_spaces + 1, len );
+                }
+            }
+
+            if s.contains( "\r\n" ) {
+                s = Cow::Owned( s.replace( "\r\n", "\n" ) );
+            }
+
+            s
+        } ) )
+        .collect()
+}
+
+fn cut( s: Cow< str >, start: usize, end: usize ) -> Cow< str > {
+    match s {
+        Cow::Borrowed( s ) => Cow::Borrowed( &s[ start .. end ] ),
+        Cow::Owned( s ) => Cow::Owned( s[ start .. end ].to_string() ),
+    }
+}
+
+//  Contents of `r"..."` or `r#"..."#`.
+//
+pub fn raw_string_value( text: &str ) -> &str {
+    let n_hashes = text[ 1 .. ].len() - text[ 1 .. ].trim_start_matches( '#' ).len();
+    let body = &text[ ( 1 + n_hashes + 1 ).min( text.len() ) .. ];
+    let closing = format!( "\"{}", "#".repeat( n_hashes ) );
+
+    body.strip_suffix( closing.as_str() ).unwrap_or( body )
+}
+
+#[ cfg( test ) ]
+mod tests {
+    use super::*;
+
+    fn unescape_with_errors( text: &str ) -> ( String, Vec< ( usize, usize, LexerErrorKind ) > ) {
+        let mut errors = Vec::new();
+        let value = unescape( text, | start, end, kind | errors.push( ( start, end, kind ) ) );
+
+        ( value.into_owned(), errors )
+    }
+
+    #[ test ]
+    fn test_unescape() {
+        assert!( matches!( unescape( "abc", | _, _, _ | {} ), Cow::Borrowed( "abc" ) ) );
+
+        assert_eq!( unescape_with_errors( r#"a\"b\\c\nd\te\${"# ), ( "a\"b\\c\nd\te${".to_string(), vec![] ) );
+        assert_eq!( unescape_with_errors( r"\u{41}\u{444}\u{1F600}" ), ( "Aф😀".to_string(), vec![] ) );
+
+        assert_eq!( unescape_with_errors( r"a\qb" ), ( r"a\qb".to_string(), vec![
+            ( 1, 3, LexerErrorKind::InvalidEscape( Some( 'q' ) ) ),
+        ] ) );
+        assert_eq!( unescape_with_errors( r"\u{D800}\u41\u{}" ), ( r"\u{D800}\u41\u{}".to_string(), vec![
+            ( 0, 8, LexerErrorKind::InvalidUnicodeEscape ),
+            ( 8, 10, LexerErrorKind::InvalidUnicodeEscape ),
+            ( 12, 16, LexerErrorKind::InvalidUnicodeEscape ),
+        ] ) );
+        assert_eq!( unescape_with_errors( "a\\" ), ( "a\\".to_string(), vec![
+            ( 1, 2, LexerErrorKind::InvalidEscape( None ) ),
+        ] ) );
+    }
+
+    #[ test ]
+    fn test_strip_indent() {
+        let strip = | parts: &[ Option< &str > ] | -> Vec< Option< String > > {
+            strip_indent( parts ).into_iter().map( | x | x.map( | x | x.into_owned() ) ).collect()
+        };
+
+        assert_eq!( strip( &[ Some( "\n    Hello,\n      world!\n    " ) ] ), vec![ Some( "Hello,\n  world!".to_string() ) ] );
+        assert_eq!( strip( &[ Some( "one line" ) ] ), vec![ Some( "one line".to_string() ) ] );
+        assert_eq!( strip( &[ Some( "\n        " ) ] ), vec![ Some( "".to_string() ) ] );
+        assert_eq!( strip( &[ Some( "\n    a\n\n    b\n" ) ] ), vec![ Some( "a\n\nb".to_string() ) ] );
+
+        assert_eq!( strip( &[ Some( "\n    Hello,\n      " ), None, Some( "!\n    " ) ] ), vec![
+            Some( "Hello,\n  ".to_string() ),
+            None,
+            Some( "!".to_string() ),
+        ] );
+        assert_eq!( strip( &[ None, Some( "\n  x\n  " ) ] ), vec![ None, Some( "\nx".to_string() ) ] );
+        assert_eq!( strip( &[ Some( "\r\n    a\r\n\r\n      b\r\n    " ) ] ), vec![ Some( "a\n\n  b".to_string() ) ] );
+    }
+
+    #[ test ]
+    fn test_raw_string_value() {
+        assert_eq!( raw_string_value( r#"r"C:\path""# ), r"C:\path" );
+        assert_eq!( raw_string_value( r###"r##"say "#hi#""##"### ), r##"say "#hi#""## );
+        assert_eq!( raw_string_value( r#"r"open"# ), "open" );
+    }
+
+}
@@ -0,0 +1,413 @@
+use std::fmt;
+
+use crate::{ SyntaxNode, SyntaxKind, TokenKind };
+use crate::ast::literal_kind;
+use crate::grammar::ASSIGN_OPS;
+use crate::number::{ NumberError, parse_number };
+use crate::format_spec::{ FormatSpecError, ValueKind, parse_format_spec };
+
+//  Checks of the tree that the grammar doesn't do: `break` and `continue` have to be inside a loop
+//  (with the label if any), `return` has to be inside a function or a closure, only places can be
+//  assigned to, number literals have to fit into their types, format specs of interpolations
+//  have to fit the types of literals and of variables with known types.
+//
+
+#[ derive( Clone ) ]
+pub struct ValidationError {
+    pub start: usize,
+    pub end: usize,
+    pub kind: ValidationErrorKind,
+}
+
+impl fmt::Debug for ValidationError {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        write!( f , "ERROR: {:?} at {}..{}", self.kind, self.start, self.end )
+    }
+
+}
+
+#[ derive( Clone, PartialEq ) ]
+pub enum ValidationErrorKind {
+    BreakOutsideLoop,
+    ContinueOutsideLoop,
+    ReturnOutsideFn,
+    UnknownLabel( String ),
+    InvalidAssignTarget,
+    InvalidNumber( NumberError ),
+    InvalidFormatSpec( FormatSpecError ),
+}
+
+impl fmt::Debug for ValidationErrorKind {
+
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match self {
+            Self::BreakOutsideLoop => write!( f, "`break` outside of a loop" ),
+            Self::ContinueOutsideLoop => write!( f, "`continue` outside of a loop" ),
+            Self::ReturnOutsideFn => write!( f, "`return` outside of a function" ),
+            Self::UnknownLabel( label ) => write!( f, "Unknown loop label {}", label ),
+            Self::InvalidAssignTarget => write!( f, "Left side of assignment is not a variable, a field or an index" ),
+            Self::InvalidNumber( error ) => write!( f, "{:?}", error ),
+            Self::InvalidFormatSpec( error ) => write!( f, "{:?}", error ),
+        }
+    }
+
+}
+
+//  ---------------------------------------------------------------------------------------------------------------  //
+
+pub fn validate( root: &SyntaxNode ) -> Vec< ValidationError > {
+    let mut validator = Validator {
+        in_fn: false,
+        loops: Vec::new(),
+        vars: Vec::new(),
+        errors: Vec::new(),
+    };
+    validator.node( root );
+
+    validator.errors
+}
+
+struct Validator< 'a > {
+    in_fn: bool,
+    //  Labels of enclosing loops of the current function, innermost last.
+    loops: Vec< Option< &'a str > >,
+    //  Variables in scope with their types if known, innermost last.
+    vars: Vec< ( &'a str, Option< ValueKind > ) >,
+    errors: Vec< ValidationError >,
+}
+
+impl < 'a > Validator< 'a > {
+
+    fn node( &mut self, node: &'a SyntaxNode< 'a > ) {
+        match node.kind {
+            SyntaxKind::Fn | SyntaxKind::StructMethod | SyntaxKind::TraitMethod | SyntaxKind::Closure => {
+                //  Loops outside of the function can't be broken from inside.
+                let loops = std::mem::take( &mut self.loops );
+                let in_fn = std::mem::replace( &mut self.in_fn, true );
+                let n_vars = self.vars.len();
+
+                let params = node.nodes().filter( | x | matches!( x.kind, SyntaxKind::FnParams | SyntaxKind::ClosureParams ) );
+                for param in params.flat_map( | x | x.nodes() ) {
+                    if let Some( name ) = param.find_token( TokenKind::Id ) {
+                        let kind = param.find_node_by_index( SyntaxKind::Type, 0 ).and_then( type_kind );
+                        self.vars.push( ( name.text, kind ) );
+                    }
+                }
+
+                self.children( node );
+
+                self.vars.truncate( n_vars );
+                self.loops = loops;
+                self.in_fn = in_fn;
+            }
+
+            SyntaxKind::While | SyntaxKind::For => {
+                let label = node
+                    .find_node_by_index( SyntaxKind::LoopLabel, 0 )
+                    .and_then( | label | label.find_token( TokenKind::Label ) )
+                    .map( | token | &token.text[ 1 .. ] );
+                let n_vars = self.vars.len();
+
+                if let Some( pattern ) = node.nodes().find( | x | is_pattern( x.kind ) ) {
+                    self.bind_pattern( pattern );
+                }
+
+                self.loops.push( label );
+                self.children( node );
+                self.loops.pop();
+                self.vars.truncate( n_vars );
+            }
+
+            SyntaxKind::Block | SyntaxKind::MatchArm => {
+                let n_vars = self.vars.len();
+
+                if let Some( pattern ) = node.nodes().next().filter( | x | is_pattern( x.kind ) ) {
+                    self.bind_pattern( pattern );
+                }
+
+                self.children( node );
+                self.vars.truncate( n_vars );
+            }
+
+            SyntaxKind::Let => {
+                self.children( node );
+
+                if let Some( name ) = node.name() {
+                    let kind = match node.find_node_by_index( SyntaxKind::Type, 0 ) {
+                        Some( ty ) => type_kind( ty ),
+                        None => node.nodes().last().and_then( | value | self.value_kind( value ) ),
+                    };
+                    self.vars.push( ( name, kind ) );
+                }
+            }
+
+            SyntaxKind::StringExpr => {
+                //  Invalid specs are reported by the lexer.
+                let spec = node.find_token( TokenKind::FormatSpec );
+                let format = spec.and_then( | spec | parse_format_spec( &spec.text[ 1 .. ] ).ok() );
+                let kind = node.nodes().next().and_then( | expr | self.value_kind( expr ) );
+
+                if let ( Some( spec ), Some( format ), Some( kind ) ) = ( spec, format, kind ) {
+                    if let Err( e ) = format.check( kind ) {
+                        self.errors.push( ValidationError {
+                            start: spec.start,
+                            end: spec.end,
+                            kind: ValidationErrorKind::InvalidFormatSpec( e ),
+                        } );
+                    }
+                }
+
+                self.children( node );
+            }
+
+            SyntaxKind::Break | SyntaxKind::Continue => {
+                let ( keyword, outside ) = if node.kind == SyntaxKind::Break {
+                    ( TokenKind::Break, ValidationErrorKind::BreakOutsideLoop )
+                } else {
+                    ( TokenKind::Continue, ValidationErrorKind::ContinueOutsideLoop )
+                };
+
+                if self.loops.is_empty() {
+                    self.error( node, keyword, outside );
+
+                } else if let Some( label ) = node.find_token( TokenKind::Label ) {
+                    let name = &label.text[ 1 .. ];
+                    if !self.loops.contains( &Some( name ) ) {
+                        self.errors.push( ValidationError {
+                            start: label.start,
+                            end: label.end,
+                            kind: ValidationErrorKind::UnknownLabel( label.text.to_string() ),
+                        } );
+                    }
+                }
+
+                self.children( node );
+            }
+
+            SyntaxKind::Return => {
+                if !self.in_fn {
+                    self.error( node, TokenKind::Return, ValidationErrorKind::ReturnOutsideFn );
+                }
+
+                self.children( node );
+            }
+
+            SyntaxKind::Assign => {
+                let target = node.nodes().next();
+
+                if !target.is_some_and( is_place ) {
+                    if let Some( op ) = node.find_token_in_set( ASSIGN_OPS ) {
+                        self.errors.push( ValidationError {
+                            start: op.start,
+                            end: op.end,
+                            kind: ValidationErrorKind::InvalidAssignTarget,
+                        } );
+                    }
+                }
+
+                self.children( node );
+            }
+
+            //  `-128i8` is the minimum of `i8`, not the negated overflowing `128i8`.
+            SyntaxKind::InlineUnary if node.find_token( TokenKind::Minus ).is_some() => {
+                match node.nodes().next() {
+                    Some( operand ) if operand.kind == SyntaxKind::InlineNumber => self.number( operand, true ),
+                    _ => self.children( node ),
+                }
+            }
+
+            SyntaxKind::InlineNumber => self.number( node, false ),
+
+            _ => self.children( node ),
+        }
+    }
+
+    fn children( &mut self, node: &'a SyntaxNode< 'a > ) {
+        for child in node.nodes() {
+            self.node( child );
+        }
+    }
+
+    fn number( &mut self, node: &SyntaxNode, negative: bool ) {
+        if let Some( token ) = node.find_token( TokenKind::Number ) {
+            if let Err( e ) = parse_number( token.text, negative ) {
+                self.errors.push( ValidationError {
+                    start: token.start,
+                    end: token.end,
+                    kind: ValidationErrorKind::InvalidNumber( e ),
+                } );
+            }
+        }
+    }
+
+    //  Names bound by patterns of `for` and `match` have unknown types, but they hide outer variables.
+    //
+    fn bind_pattern( &mut self, pattern: &'a SyntaxNode< 'a > ) {
+        match pattern.kind {
+            SyntaxKind::BindingPattern => self.vars.extend( pattern.name().map( | name | ( name, None ) ) ),
+            //  `{ x }` binds the field `x`, `{ x: pattern }` binds names of the pattern.
+            SyntaxKind::RecordPatternField if pattern.nodes().count() == 1 => self.vars.extend( pattern.name().map( | name | ( name, None ) ) ),
+            _ => {},
+        }
+
+        for child in pattern.nodes() {
+            self.bind_pattern( child );
+        }
+    }
+
+    //  Type of a literal or of a variable, if known.
+    //
+    fn value_kind( &self, expr: &SyntaxNode ) -> Option< ValueKind > {
+        match expr.kind {
+            SyntaxKind::InlineVar => {
+                let name = expr.find_token( TokenKind::Id )?.text;
+                self.vars.iter().rev().find( | ( x, _ ) | *x == name ).and_then( | ( _, kind ) | *kind )
+            }
+            SyntaxKind::Expr | SyntaxKind::InlineSubexpr => self.value_kind( expr.nodes().next()? ),
+            _ => literal_kind( expr ),
+        }
+    }
+
+    //  Reported at the keyword.
+    fn error( &mut self, node: &SyntaxNode, keyword: TokenKind, kind: ValidationErrorKind ) {
+        if let Some( token ) = node.find_token( keyword ) {
+            self.errors.push( ValidationError {
+                start: token.start,
+                end: token.end,
+                kind,
+            } );
+        }
+    }
+
+}
+
+fn is_pattern( kind: SyntaxKind ) -> bool {
+    matches!( kind,
+        SyntaxKind::WildcardPattern | SyntaxKind::BindingPattern | SyntaxKind::LiteralPattern | SyntaxKind::TuplePattern |
+        SyntaxKind::VariantPattern | SyntaxKind::RecordPattern | SyntaxKind::OrPattern
+    )
+}
+
+//  Types of values which can be formatted, other types are unknown.
+//
+fn type_kind( ty: &SyntaxNode ) -> Option< ValueKind > {
+    let name = ty.find_node_by_index( SyntaxKind::TypeRef, 0 )?.find_token( TokenKind::Id )?.text;
+
+    match name {
+        "number" => Some( ValueKind::Number ),
+        "string" => Some( ValueKind::String ),
+        "bool" => Some( ValueKind::Bool ),
+        "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" => Some( ValueKind::Int ),
+        "f32" | "f64" => Some( ValueKind::Float ),
+        _ => None,
+    }
+}
+
+//  Expression that can be assigned to.
+//
+fn is_place( node: &SyntaxNode ) -> bool {
+    matches!( node.kind, SyntaxKind::InlineVar | SyntaxKind::InlineField | SyntaxKind::InlineTupleField | SyntaxKind::InlineIndex )
+}
+
+#[ cfg( test ) ]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::grammar::r_source_file;
+    use crate::number::NumberSuffix;
+
+    fn errors( input: &str ) -> Vec< ( ValidationErrorKind, &str ) > {
+        let node = Parser::new( input ).parse( r_source_file );
+
+        validate( &node )
+            .into_iter()
+            .map( | error | ( error.kind, &input[ error.start .. error.end ] ) )
+            .collect()
+    }
+
+    #[ test ]
+    fn jumps() {
+        assert_eq!( errors( "fn f() {\n    'a: while x {\n        for y in ys {\n            break 'a\n            continue\n        }\n    }\n    return 1\n}\n" ), vec![] );
+
+        assert_eq!( errors( "fn f() {\n    break\n    if x { continue }\n    return\n}\n" ), vec![
+            ( ValidationErrorKind::BreakOutsideLoop, "break" ),
+            ( ValidationErrorKind::ContinueOutsideLoop, "continue" ),
+        ] );
+
+        assert_eq!( errors( "fn f() {\n    'a: for x in xs { break 'b }\n    while x { continue 'a }\n}\n" ), vec![
+            ( ValidationErrorKind::UnknownLabel( "'b".to_string() ), "'b" ),
+            ( ValidationErrorKind::UnknownLabel( "'a".to_string() ), "'a" ),
+        ] );
+    }
+
+    #[ test ]
+    fn assignments() {
+        assert_eq!( errors( "fn f() {\n    x = x + 1\n    p.x += 1\n    a = b = c\n    xs[ i ] = t.0\n    t.1 *= 2\n}\n" ), vec![] );
+
+        assert_eq!( errors( "fn f() {\n    1 = x\n    f() -= 1\n    a + b ||= c\n    ( x ) = 1\n    [ x ] = 1\n}\n" ), vec![
+            ( ValidationErrorKind::InvalidAssignTarget, "=" ),
+            ( ValidationErrorKind::InvalidAssignTarget, "-=" ),
+            ( ValidationErrorKind::InvalidAssignTarget, "||=" ),
+            ( ValidationErrorKind::InvalidAssignTarget, "=" ),
+            ( ValidationErrorKind::InvalidAssignTarget, "=" ),
+        ] );
+    }
+
+    #[ test ]
+    fn closures() {
+        assert_eq!( errors( "fn f() {\n    for x in xs {\n        g( || { return 1 } )\n        g( || { break } )\n    }\n}\n" ), vec![
+            ( ValidationErrorKind::BreakOutsideLoop, "break" ),
+        ] );
+    }
+
+    #[ test ]
+    fn numbers() {
+        assert_eq!( errors( "fn f() {\n    let x = 255u8\n    let y = 256u8 + 0b102\n    g( 1.5i32 )\n}\n" ), vec![
+            ( ValidationErrorKind::InvalidNumber( NumberError::Overflow( Some( NumberSuffix::U8 ) ) ), "256u8" ),
+            ( ValidationErrorKind::InvalidNumber( NumberError::InvalidDigit( '2' ) ), "0b102" ),
+            ( ValidationErrorKind::InvalidNumber( NumberError::InvalidSuffix( "i32".to_string() ) ), "1.5i32" ),
+        ] );
+
+        //  Minimums of signed types are negated literals.
+        assert_eq!( errors( "fn f() {\n    let x = -128i8 + -9223372036854775808\n    let y = -129i8 - -( 128i8 )\n}\n" ), vec![
+            ( ValidationErrorKind::InvalidNumber( NumberError::Overflow( Some( NumberSuffix::I8 ) ) ), "129i8" ),
+            ( ValidationErrorKind::InvalidNumber( NumberError::Overflow( Some( NumberSuffix::I8 ) ) ), "128i8" ),
+        ] );
+    }
+
+    #[ test ]
+    fn format_specs() {
+        let error = | what, kind | ValidationErrorKind::InvalidFormatSpec( FormatSpecError::NotApplicable( what, kind ) );
+
+        //  Literals, parameters and `let` with a type or initialized with a literal or a variable.
+        assert_eq!( errors( "fn f( price: number, n: u8, name: string ) {\n    let s = \"s\"\n    let t = ( s )\n    let ok: bool = g()\n    \"${ 42:.2 } ${ price:+.2 } ${ n:#x } ${ name:+ } ${ t:05 } ${ ok:.1 }\"\n}\n" ), vec![
+            ( error( "precision", ValueKind::Int ), ":.2" ),
+            ( error( "sign", ValueKind::String ), ":+" ),
+            ( error( "zero padding", ValueKind::String ), ":05" ),
+            ( error( "precision", ValueKind::Bool ), ":.1" ),
+        ] );
+
+        //  Unknown types, names bound by patterns and closure parameters hide outer variables.
+        assert_eq!( errors( "fn f( s: string, xs: Items ) {\n    let v = g()\n    \"${ v:+ } ${ xs:+ }\"\n    for s in xs { \"${ s:+ }\" }\n    match xs { Some( s ) => \"${ s:+ }\", Point { s } => s }\n    h( | s | \"${ s:+ }\" )\n    \"${ s:+ }\"\n}\n" ), vec![
+            ( error( "sign", ValueKind::String ), ":+" ),
+        ] );
+
+        //  Variables are visible in their block only.
+        assert_eq!( errors( "fn f( x: number ) {\n    if c {\n        let x = \"s\"\n        \"${ x:+ }\"\n    }\n    \"${ x:+ }\"\n}\n" ), vec![
+            ( error( "sign", ValueKind::String ), ":+" ),
+        ] );
+    }
+
+    #[ test ]
+    fn return_outside_fn() {
+        assert_eq!( errors( "const X = match y { _ => return 1 }\n" ), vec![
+            ( ValidationErrorKind::ReturnOutsideFn, "return" ),
+        ] );
+
+        assert_eq!( errors( "struct S {\n    x: number = match y { _ => return 1 }\n    fn f() { return 2 }\n}\n" ), vec![
+            ( ValidationErrorKind::ReturnOutsideFn, "return" ),
+        ] );
+    }
+
+}